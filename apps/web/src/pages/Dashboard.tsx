@@ -33,7 +33,7 @@ export default function Dashboard() {
       });
 
       // Get user's cycles
-      const { result: userCycles } = await contract.get_user_cycles({ user: address });
+      const { result: userCycles } = await contract.get_user_cycles({ user: address, token: undefined });
 
       const dueSoon: any[] = [];
       const now = Date.now() / 1000;
@@ -83,6 +83,7 @@ export default function Dashboard() {
 
       const { result } = await contract.get_user_cycles({
         user: address,
+        token: undefined,
       });
       setCycles(result);
     } catch (error) {
@@ -105,8 +106,13 @@ export default function Dashboard() {
         publicKey: address,
       });
 
+      // New cycles are funded in the contract's default token
+      const { result: token } = await contract.usdc_token();
+      const tokenAddress = (token as any)?.unwrap?.() ?? token;
+
       const tx = await contract.create_cycle({
         user: address,
+        token: tokenAddress,
         duration_months: parseInt(durationMonths),
         amount: amountInStroops,
       });
@@ -510,6 +516,7 @@ interface BillForm {
   recurrenceDays: number[];
   isEmergency: boolean;
   category: string;
+  payee: string;
 }
 
 function getDaySuffix(day: number): string {
@@ -569,7 +576,8 @@ function CycleCard({ cycleId }: { cycleId: bigint }) {
       isRecurring: false,
       recurrenceDays: [],
       isEmergency: false,
-      category: "Other"
+      category: "Other",
+      payee: ""
     }]);
   };
 
@@ -718,7 +726,7 @@ function CycleCard({ cycleId }: { cycleId: bigint }) {
     }
 
     for (const bill of bills) {
-      if (!bill.name || !bill.amount || !bill.dueDate) {
+      if (!bill.name || !bill.amount || !bill.dueDate || !bill.payee) {
         alert("Please fill in all fields for each bill");
         return;
       }
@@ -839,7 +847,7 @@ function CycleCard({ cycleId }: { cycleId: bigint }) {
         // Use the user-selected category from the dropdown
         const category: BillCategory = { tag: bill.category as any, values: void 0 };
 
-        return [bill.name, amountInStroops, dueDateTimestamp, bill.isRecurring, recurrenceCalendar, category];
+        return [bill.name, amountInStroops, dueDateTimestamp, bill.isRecurring, recurrenceCalendar, category, bill.payee];
 
       });
 
@@ -1193,6 +1201,27 @@ function CycleCard({ cycleId }: { cycleId: bigint }) {
                   />
                 </div>
 
+                <div>
+                  <label style={{ display: 'block', marginBottom: '8px', fontSize: '13px', fontWeight: 600, color: 'var(--color-text-primary)' }}>
+                    Payee Address *
+                  </label>
+                  <input
+                    placeholder="G... address that receives the payment"
+                    value={bill.payee}
+                    onChange={(e) => updateBillForm(index, "payee", e.target.value.trim())}
+                    style={{
+                      width: '100%',
+                      padding: '10px',
+                      fontSize: '14px',
+                      backgroundColor: 'var(--color-bg-primary)',
+                      border: '1px solid var(--color-border)',
+                      borderRadius: 'var(--radius-md)',
+                      color: 'var(--color-text-primary)',
+                      transition: 'var(--transition-base)'
+                    }}
+                  />
+                </div>
+
                 <div>
                   <label style={{ display: 'block', marginBottom: '8px', fontSize: '13px', fontWeight: 600, color: 'var(--color-text-primary)' }}>
                     Category *
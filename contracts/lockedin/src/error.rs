@@ -7,10 +7,16 @@ pub enum Error {
     // Authorization errors
     Unauthorized = 1,
     AdminNotSet = 2,
+    RoleNotGranted = 3,
+    NotSigner = 4,
+    InsufficientApprovals = 5,
+    InvalidThreshold = 6,
+    AlreadyApproved = 7,
+    ApprovalNotFound = 8,
+    SignerAlreadyExists = 9,
 
     // Cycle errors
     CycleNotFound = 10,
-    CycleAlreadyExists = 11,
     CycleNotActive = 12,
     CycleAlreadyEnded = 13,
     InvalidCycleDuration = 14,
@@ -25,16 +31,18 @@ pub enum Error {
     EmergencyBillLimitExceeded = 25,
     MonthlyAdjustmentLimitReached = 26,
     InvalidRecurrence = 27,
+    InvalidPaymentWindow = 28,
+    BillAmountNotSet = 29,
 
     // Time-lock errors
     CycleNotEnded = 30,
     EarlyWithdrawalNotAllowed = 31,
     BillNotDueYet = 32,
 
-    // Admin transfer errors
-    NoPendingAdminTransfer = 40,
-    AdminTransferExpired = 41,
-    PendingAdminTransferExists = 42,
+    // Governance errors
+    ProposalNotFound = 43,
+    TimelockNotElapsed = 44,
+    ProposalExpired = 45,
 
     // Input validation errors
     InvalidFeePercentage = 50,
@@ -44,11 +52,27 @@ pub enum Error {
     // Configuration errors
     FeeRecipientNotSet = 53,
     UsdcTokenNotSet = 54,
-    FeePercentageNotSet = 55,
+    DefaultTokenNotRemovable = 55,
+    InvalidKeeperTip = 56,
+    TokenNotAllowed = 57,
+    TokenAlreadyAllowed = 58,
+    InvalidPenalty = 59,
 
     // Security errors
     Reentrancy = 60,
+    ContractPaused = 61,
+    NotPaused = 62,
 
     // Token transfer errors
     TokenTransferFailed = 70,
-}
\ No newline at end of file
+
+    // Upgrade errors
+    AlreadyMigrated = 80,
+    MigrationInProgress = 81,
+
+    // Invoice errors
+    InvoiceNotFound = 90,
+    InvoiceAlreadyPending = 91,
+    InvoiceAlreadyApproved = 92,
+    InvoicePendingApproval = 93,
+}
@@ -1,14 +1,69 @@
-use soroban_sdk::{contractevent, Address};
+use soroban_sdk::{contractevent, Address, BytesN, String};
+
+use crate::types::{AdminAction, BillCategory, ConfigChange, PauseScope, Role};
 
 // admin events
 #[contractevent]
-pub struct AdminTransferInitiated {
-    pub new_admin: Address,
+pub struct SignerAdded {
+    pub signer: Address,
+}
+
+#[contractevent]
+pub struct SignerRemoved {
+    pub signer: Address,
+}
+
+#[contractevent]
+pub struct ThresholdChanged {
+    pub threshold: u32,
+}
+
+#[contractevent]
+pub struct AdminActionApproved {
+    pub action: AdminAction,
+    pub signer: Address,
+}
+
+#[contractevent]
+pub struct AdminApprovalRevoked {
+    pub action: AdminAction,
+    pub signer: Address,
+}
+
+#[contractevent]
+pub struct RoleGranted {
+    pub role: Role,
+    pub account: Address,
 }
 
 #[contractevent]
-pub struct AdminTransferred {
-    pub new_admin: Address,
+pub struct RoleRevoked {
+    pub role: Role,
+    pub account: Address,
+}
+
+#[contractevent]
+pub struct ConfigChangeProposed {
+    pub proposal_id: u64,
+    pub change: ConfigChange,
+    pub executable_from: u32,
+    pub expires_at: u32,
+}
+
+#[contractevent]
+pub struct ConfigChangeExecuted {
+    pub proposal_id: u64,
+}
+
+#[contractevent]
+pub struct ConfigChangeCancelled {
+    pub proposal_id: u64,
+}
+
+#[contractevent]
+pub struct FeePercentageUpdated {
+    pub token: Address,
+    pub fee_percentage: u32,
 }
 
 #[contractevent]
@@ -16,6 +71,67 @@ pub struct FeeRecipientUpdated {
     pub recipient: Address,
 }
 
+#[contractevent]
+pub struct UsdcTokenUpdated {
+    pub token: Address,
+}
+
+#[contractevent]
+pub struct TokenAllowed {
+    pub token: Address,
+    pub fee_percentage: u32,
+}
+
+#[contractevent]
+pub struct TokenDisallowed {
+    pub token: Address,
+}
+
+#[contractevent]
+pub struct PaymentWindowUpdated {
+    pub days_before: u32,
+    pub days_after: u32,
+}
+
+#[contractevent]
+pub struct KeeperTipUpdated {
+    pub tip: i128,
+    pub fee_share_bps: u32,
+}
+
+#[contractevent]
+pub struct EarlyWithdrawalPenaltyUpdated {
+    pub penalty_bps: u32,
+}
+
+#[contractevent]
+pub struct SolvencyAlert {
+    pub token: Address,
+    pub balance: i128,
+    pub liabilities: i128,
+    pub shortfall: i128,
+}
+
+#[contractevent]
+pub struct ContractUpgraded {
+    pub new_wasm_hash: BytesN<32>,
+}
+
+#[contractevent]
+pub struct StorageMigrated {
+    pub version: u32,
+}
+
+#[contractevent]
+pub struct Paused {
+    pub scope: PauseScope,
+}
+
+#[contractevent]
+pub struct Unpaused {
+    pub scope: PauseScope,
+}
+
 // billing cycle events
 #[contractevent]
 pub struct CycleCreated {
@@ -23,6 +139,39 @@ pub struct CycleCreated {
     pub user: Address,
 }
 
+#[contractevent]
+pub struct CycleDeposit {
+    pub cycle_id: u64,
+    pub amount: i128,
+    pub operating_fee: i128,
+}
+
+#[contractevent]
+pub struct EmergencyWithdrawal {
+    pub cycle_id: u64,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct EarlyWithdrawal {
+    pub cycle_id: u64,
+    pub amount: i128,
+    pub penalty: i128,
+}
+
+#[contractevent]
+pub struct AutoRenewUpdated {
+    pub cycle_id: u64,
+    pub enabled: bool,
+}
+
+#[contractevent]
+pub struct CycleRenewed {
+    pub cycle_id: u64,
+    pub new_cycle_id: u64,
+    pub rolled_over: i128,
+}
+
 #[contractevent]
 pub struct CycleEnded {
     pub cycle_id: u64,
@@ -36,13 +185,74 @@ pub struct BillAdded {
     pub cycle_id: u64,
 }
 
+#[contractevent]
+pub struct EmergencyBillAdded {
+    pub bill_id: u64,
+    pub cycle_id: u64,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct BillUpdated {
+    pub bill_id: u64,
+    pub old_name: String,
+    pub new_name: String,
+    pub old_amount: i128,
+    pub new_amount: i128,
+    pub old_due_date: u64,
+    pub new_due_date: u64,
+    pub old_category: BillCategory,
+    pub new_category: BillCategory,
+}
+
+#[contractevent]
+pub struct BillAmountSet {
+    pub bill_id: u64,
+    pub amount: i128,
+    pub set_by: Address,
+}
+
 #[contractevent]
 pub struct BillPaid {
     pub bill_id: u64,
     pub amount: i128,
+    pub payee: Address,
+    pub is_late: bool,
 }
 
 #[contractevent]
 pub struct BillCancelled {
     pub bill_id: u64,
-}
\ No newline at end of file
+}
+
+#[contractevent]
+pub struct KeeperTipPaid {
+    pub bill_id: u64,
+    pub keeper: Address,
+    pub tip: i128,
+}
+
+// invoice events
+#[contractevent]
+pub struct InvoiceSubmitted {
+    pub bill_id: u64,
+    pub payee: Address,
+    pub amount: i128,
+    pub reference: String,
+}
+
+#[contractevent]
+pub struct InvoiceApproved {
+    pub bill_id: u64,
+    pub amount: i128,
+}
+
+#[contractevent]
+pub struct InvoiceRejected {
+    pub bill_id: u64,
+}
+
+#[contractevent]
+pub struct InvoiceDropped {
+    pub bill_id: u64,
+}
@@ -5,16 +5,46 @@ mod events;
 mod test;
 mod types;
 
-use soroban_sdk::{contract, contractimpl, token, Address, Env, String, Vec};
+use soroban_sdk::{
+    contract, contractimpl, token, Address, BytesN, Env, Map, String, Symbol, TryFromVal, Val, Vec,
+};
 use time::OffsetDateTime;
 
 use error::Error;
-use types::{Bill, BillCategory, BillCycle, DataKey};
+use types::{
+    AdminAction, Bill, BillCategory, BillCycle, BillCycleV2, BillCycleV3, BillInput, BillV2,
+    BillV3, ConfigChange, ConfigProposal, CycleSummary, DataKey, InstallmentPlan, Invoice,
+    KeeperTip, PauseScope, PaymentResult, PaymentWindow, Role, SolvencyReport,
+};
+
+// Storage layout version written by this code; bump it and extend migrate_cycle when
+// a stored struct gains fields or a new aggregate has to be rebuilt from the cycles
+const CONTRACT_VERSION: u32 = 9;
 
 const DAY_IN_LEDGERS: u32 = 17280; // ~24 hours
 const LEDGER_TTL_THRESHOLD: u32 = DAY_IN_LEDGERS * 30; // 30 days
 const LEDGER_TTL_EXTEND: u32 = DAY_IN_LEDGERS * 365; // 1 year
 
+// Config changes wait ~2 days after being proposed and can be executed for ~7 days after that
+const CONFIG_TIMELOCK_LEDGERS: u32 = DAY_IN_LEDGERS * 2;
+const CONFIG_PROPOSAL_LIFETIME_LEDGERS: u32 = DAY_IN_LEDGERS * 7;
+
+// Payment window around a bill's due date
+const MIN_LEAD_TIME_DAYS: u32 = 7;
+const DEFAULT_PAYMENT_GRACE_DAYS: u32 = 3;
+const MAX_PAYMENT_GRACE_DAYS: u32 = 28;
+
+// Longest range get_due_bills will scan in one call
+const MAX_DUE_BILLS_RANGE_DAYS: u64 = 31;
+
+// Emergency bills skip the lead time rules, so they are capped per cycle
+const MAX_EMERGENCY_BILLS_PER_CYCLE: u32 = 2;
+const MAX_EMERGENCY_ALLOCATION_BPS: i128 = 2500; // 25% of available funds
+
+// Share of an early withdrawal kept as a penalty and sent to the fee recipient
+const DEFAULT_EARLY_WITHDRAWAL_PENALTY_BPS: u32 = 1000; // 10%
+const MAX_EARLY_WITHDRAWAL_PENALTY_BPS: u32 = 5000;
+
 // RAII reentrancy guard - automatically releases lock when dropped
 struct ReentrancyGuard<'a> {
     env: &'a Env,
@@ -25,14 +55,19 @@ impl<'a> ReentrancyGuard<'a> {
         if env.storage().persistent().has(&DataKey::ReentrancyLock) {
             return Err(Error::Reentrancy);
         }
-        env.storage().persistent().set(&DataKey::ReentrancyLock, &true);
+        env.storage()
+            .persistent()
+            .set(&DataKey::ReentrancyLock, &true);
         Ok(ReentrancyGuard { env })
     }
 }
 
 impl<'a> Drop for ReentrancyGuard<'a> {
     fn drop(&mut self) {
-        self.env.storage().persistent().remove(&DataKey::ReentrancyLock);
+        self.env
+            .storage()
+            .persistent()
+            .remove(&DataKey::ReentrancyLock);
     }
 }
 
@@ -44,145 +79,311 @@ impl LockedIn {
     pub fn __constructor(env: Env, admin: Address, usdc_token: Address) {
         admin.require_auth();
 
-        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(
+            &DataKey::AdminSigners,
+            &Vec::from_array(&env, [admin.clone()]),
+        );
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminThreshold, &1u32);
         env.storage()
             .instance()
             .set(&DataKey::UsdcToken, &usdc_token);
         env.storage().instance().set(&DataKey::FeeRecipient, &admin);
+        env.storage().instance().set(
+            &DataKey::AllowedTokens,
+            &Vec::from_array(&env, [usdc_token.clone()]),
+        );
         env.storage()
             .instance()
-            .set(&DataKey::FeePercentage, &200u32); // Default 2% fee
+            .set(&DataKey::TokenFeePercentage(usdc_token), &200u32); // Default 2% fee
         env.storage().instance().set(&DataKey::CycleCounter, &0u64);
         env.storage().instance().set(&DataKey::BillCounter, &0u64);
+        env.storage()
+            .instance()
+            .set(&DataKey::Version, &CONTRACT_VERSION);
     }
 
     // Admin functions
+    // The admin is a set of signers; an admin action runs once `threshold` of them
+    // have approved that exact action with approve_admin_action
 
-    pub fn admin(env: Env) -> Result<Address, Error> {
+    // Deployments from before the signer set report the single legacy admin
+    pub fn admin_signers(env: Env) -> Vec<Address> {
+        if let Some(signers) = env.storage().instance().get(&DataKey::AdminSigners) {
+            return signers;
+        }
+        match env.storage().instance().get(&DataKey::Admin) {
+            Some(admin) => Vec::from_array(&env, [admin]),
+            None => Vec::new(&env),
+        }
+    }
+
+    pub fn admin_threshold(env: Env) -> u32 {
         env.storage()
             .instance()
-            .get(&DataKey::Admin)
-            .ok_or(Error::AdminNotSet)
+            .get(&DataKey::AdminThreshold)
+            .unwrap_or(1)
     }
 
-    // Initiate admin transfer (step 1 of 2-step transfer)
-    pub fn transfer_admin(
+    // Record a signer's approval; approvals are used up when the action runs
+    pub fn approve_admin_action(
         env: Env,
-        new_admin: Address,
-        live_until_ledger: u32,
+        signer: Address,
+        action: AdminAction,
     ) -> Result<(), Error> {
-        Self::require_admin(&env)?;
+        signer.require_auth();
+        if !Self::admin_signers(env.clone()).contains(&signer) {
+            return Err(Error::NotSigner);
+        }
 
-        // Check if there's already a pending transfer that hasn't expired
-        if let Some(expiry) = env
-            .storage()
-            .instance()
-            .get::<DataKey, u32>(&DataKey::TransferExpiry)
-        {
-            // If there's a non-expired pending transfer, prevent overwriting it
-            if env.ledger().sequence() <= expiry {
-                return Err(Error::PendingAdminTransferExists);
-            }
+        let approvals_key = DataKey::AdminApprovals(action.clone());
+        let mut approvals = Self::get_admin_approvals(env.clone(), action.clone());
+        if approvals.contains(&signer) {
+            return Err(Error::AlreadyApproved);
+        }
+        approvals.push_back(signer.clone());
+        env.storage().persistent().set(&approvals_key, &approvals);
+        Self::extend_ttl(&env, &approvals_key);
+
+        events::AdminActionApproved { action, signer }.publish(&env);
+
+        Ok(())
+    }
+
+    pub fn revoke_admin_approval(
+        env: Env,
+        signer: Address,
+        action: AdminAction,
+    ) -> Result<(), Error> {
+        signer.require_auth();
+
+        let approvals_key = DataKey::AdminApprovals(action.clone());
+        let mut approvals = Self::get_admin_approvals(env.clone(), action.clone());
+        let index = approvals
+            .first_index_of(&signer)
+            .ok_or(Error::ApprovalNotFound)?;
+        approvals.remove(index);
+        if approvals.is_empty() {
+            env.storage().persistent().remove(&approvals_key);
+        } else {
+            env.storage().persistent().set(&approvals_key, &approvals);
         }
 
+        events::AdminApprovalRevoked { action, signer }.publish(&env);
+
+        Ok(())
+    }
+
+    pub fn get_admin_approvals(env: Env, action: AdminAction) -> Vec<Address> {
         env.storage()
-            .instance()
-            .set(&DataKey::PendingAdmin, &new_admin);
+            .persistent()
+            .get(&DataKey::AdminApprovals(action))
+            .unwrap_or(Vec::new(&env))
+    }
+
+    pub fn add_signer(env: Env, signer: Address) -> Result<(), Error> {
+        Self::require_admin(&env, AdminAction::AddSigner(signer.clone()))?;
+
+        let mut signers = Self::admin_signers(env.clone());
+        if signers.contains(&signer) {
+            return Err(Error::SignerAlreadyExists);
+        }
+        signers.push_back(signer.clone());
         env.storage()
             .instance()
-            .set(&DataKey::TransferExpiry, &live_until_ledger);
+            .set(&DataKey::AdminSigners, &signers);
 
-        events::AdminTransferInitiated {
-            new_admin: new_admin.clone(),
-        }
-        .publish(&env);
+        events::SignerAdded { signer }.publish(&env);
 
         Ok(())
     }
 
-    // Accept admin transfer (step 2 of 2-step transfer)
-    pub fn accept_admin(env: Env) -> Result<(), Error> {
-        let new_admin: Address = env
-            .storage()
+    // The threshold must still be reachable by the remaining signers
+    pub fn remove_signer(env: Env, signer: Address) -> Result<(), Error> {
+        Self::require_admin(&env, AdminAction::RemoveSigner(signer.clone()))?;
+
+        let mut signers = Self::admin_signers(env.clone());
+        let index = signers.first_index_of(&signer).ok_or(Error::NotSigner)?;
+        if signers.len() - 1 < Self::admin_threshold(env.clone()) {
+            return Err(Error::InvalidThreshold);
+        }
+        signers.remove(index);
+        env.storage()
             .instance()
-            .get(&DataKey::PendingAdmin)
-            .ok_or(Error::NoPendingAdminTransfer)?;
+            .set(&DataKey::AdminSigners, &signers);
 
-        new_admin.require_auth();
+        events::SignerRemoved { signer }.publish(&env);
 
-        let expiry: u32 = env
-            .storage()
-            .instance()
-            .get(&DataKey::TransferExpiry)
-            .ok_or(Error::NoPendingAdminTransfer)?;
+        Ok(())
+    }
+
+    pub fn set_threshold(env: Env, threshold: u32) -> Result<(), Error> {
+        Self::require_admin(&env, AdminAction::SetThreshold(threshold))?;
 
-        if env.ledger().sequence() > expiry {
-            return Err(Error::AdminTransferExpired);
+        if threshold == 0 || threshold > Self::admin_signers(env.clone()).len() {
+            return Err(Error::InvalidThreshold);
         }
+        env.storage()
+            .instance()
+            .set(&DataKey::AdminThreshold, &threshold);
 
-        env.storage().instance().set(&DataKey::Admin, &new_admin);
-        env.storage().instance().remove(&DataKey::PendingAdmin);
-        env.storage().instance().remove(&DataKey::TransferExpiry);
+        events::ThresholdChanged { threshold }.publish(&env);
 
-        events::AdminTransferred {
-            new_admin: new_admin.clone(),
-        }
-        .publish(&env);
+        Ok(())
+    }
+
+    // Grant a role to an account, admin only
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        Self::require_admin(&env, AdminAction::GrantRole(role.clone(), account.clone()))?;
+
+        let role_key = DataKey::RoleMember(role.clone(), account.clone());
+        env.storage().persistent().set(&role_key, &true);
+        Self::extend_ttl(&env, &role_key);
+
+        events::RoleGranted { role, account }.publish(&env);
 
         Ok(())
     }
 
-    // Cancel a pending admin transfer
-    pub fn cancel_admin_transfer(env: Env) -> Result<(), Error> {
-        Self::require_admin(&env)?;
+    // Revoke a role from an account, admin only
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), Error> {
+        Self::require_admin(&env, AdminAction::RevokeRole(role.clone(), account.clone()))?;
 
-        if !env
-            .storage()
-            .instance()
-            .has(&DataKey::PendingAdmin)
-        {
-            return Err(Error::NoPendingAdminTransfer);
+        let role_key = DataKey::RoleMember(role.clone(), account.clone());
+        if !env.storage().persistent().has(&role_key) {
+            return Err(Error::RoleNotGranted);
         }
+        env.storage().persistent().remove(&role_key);
 
-        env.storage().instance().remove(&DataKey::PendingAdmin);
-        env.storage().instance().remove(&DataKey::TransferExpiry);
+        events::RoleRevoked { role, account }.publish(&env);
 
         Ok(())
     }
 
-    pub fn set_fee_recipient(env: Env, recipient: Address) -> Result<(), Error> {
-        Self::require_admin(&env)?;
+    // Signers don't hold roles implicitly; they act through approved admin actions
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        let role_key = DataKey::RoleMember(role, account);
+        if env.storage().persistent().has(&role_key) {
+            Self::extend_ttl(&env, &role_key);
+            return true;
+        }
+        false
+    }
+
+    // Config changes are timelocked: propose, wait CONFIG_TIMELOCK_LEDGERS, then execute
+    // Pending proposals are public so users can react before a change applies
+    pub fn propose_config_change(
+        env: Env,
+        caller: Address,
+        change: ConfigChange,
+    ) -> Result<u64, Error> {
+        Self::require_config_authority(
+            &env,
+            &caller,
+            &change,
+            AdminAction::ProposeConfigChange(change.clone()),
+        )?;
+        Self::validate_config_change(&env, &change)?;
+
+        let executable_from = env.ledger().sequence() + CONFIG_TIMELOCK_LEDGERS;
+        let expires_at = executable_from + CONFIG_PROPOSAL_LIFETIME_LEDGERS;
+
+        let proposal_id = Self::next_proposal_id(&env);
+        let proposal = ConfigProposal {
+            change: change.clone(),
+            proposer: caller,
+            executable_from,
+            expires_at,
+        };
+        env.storage()
+            .instance()
+            .set(&DataKey::Proposal(proposal_id), &proposal);
 
+        let mut pending = Self::get_pending_proposals(env.clone());
+        pending.push_back(proposal_id);
         env.storage()
             .instance()
-            .set(&DataKey::FeeRecipient, &recipient);
+            .set(&DataKey::PendingProposals, &pending);
 
-        events::FeeRecipientUpdated {
-            recipient: recipient.clone(),
+        events::ConfigChangeProposed {
+            proposal_id,
+            change,
+            executable_from,
+            expires_at,
         }
         .publish(&env);
 
+        Ok(proposal_id)
+    }
+
+    pub fn execute_config_change(env: Env, caller: Address, proposal_id: u64) -> Result<(), Error> {
+        let proposal = Self::get_proposal(env.clone(), proposal_id)?;
+        Self::require_config_authority(
+            &env,
+            &caller,
+            &proposal.change,
+            AdminAction::ExecuteConfigChange(proposal_id),
+        )?;
+
+        let sequence = env.ledger().sequence();
+        if sequence < proposal.executable_from {
+            return Err(Error::TimelockNotElapsed);
+        }
+        if sequence > proposal.expires_at {
+            return Err(Error::ProposalExpired);
+        }
+
+        // Re-check, e.g. the token may have been disallowed while the proposal waited
+        Self::validate_config_change(&env, &proposal.change)?;
+
+        Self::remove_proposal(&env, proposal_id);
+        Self::apply_config_change(&env, proposal.change);
+
+        events::ConfigChangeExecuted { proposal_id }.publish(&env);
+
         Ok(())
     }
 
-    pub fn fee_recipient(env: &Env) -> Result<Address, Error> {
+    pub fn cancel_config_change(env: Env, caller: Address, proposal_id: u64) -> Result<(), Error> {
+        let proposal = Self::get_proposal(env.clone(), proposal_id)?;
+        Self::require_config_authority(
+            &env,
+            &caller,
+            &proposal.change,
+            AdminAction::CancelConfigChange(proposal_id),
+        )?;
+
+        Self::remove_proposal(&env, proposal_id);
+
+        events::ConfigChangeCancelled { proposal_id }.publish(&env);
+
+        Ok(())
+    }
+
+    pub fn get_proposal(env: Env, proposal_id: u64) -> Result<ConfigProposal, Error> {
         env.storage()
             .instance()
-            .get(&DataKey::FeeRecipient)
-            .ok_or(Error::FeeRecipientNotSet)
+            .get(&DataKey::Proposal(proposal_id))
+            .ok_or(Error::ProposalNotFound)
     }
 
-    pub fn set_usdc_token(env: Env, usdc_token: Address) -> Result<(), Error> {
-        Self::require_admin(&env)?;
-
+    // IDs of proposals not yet executed or cancelled, including expired ones
+    pub fn get_pending_proposals(env: Env) -> Vec<u64> {
         env.storage()
             .instance()
-            .set(&DataKey::UsdcToken, &usdc_token);
+            .get(&DataKey::PendingProposals)
+            .unwrap_or(Vec::new(&env))
+    }
 
-        Ok(())
+    pub fn fee_recipient(env: &Env) -> Result<Address, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::FeeRecipient)
+            .ok_or(Error::FeeRecipientNotSet)
     }
 
-    // Get USDC token address (consolidated getter)
+    // Default token for clients; cycles from before per-cycle tokens are migrated onto it
     pub fn usdc_token(env: &Env) -> Result<Address, Error> {
         env.storage()
             .instance()
@@ -190,290 +391,379 @@ impl LockedIn {
             .ok_or(Error::UsdcTokenNotSet)
     }
 
-    pub fn set_fee_percentage(env: Env, fee_percentage: u32) -> Result<(), Error> {
-        Self::require_admin(&env)?;
+    // Allow cycles to be funded in a new token
+    // Fees of allowed tokens only change through propose_config_change
+    pub fn allow_token(env: Env, token: Address, fee_percentage: u32) -> Result<(), Error> {
+        Self::require_admin(&env, AdminAction::AllowToken(token.clone(), fee_percentage))?;
 
         Self::validate_fee_percentage(fee_percentage)?;
 
+        let mut allowed_tokens = Self::allowed_tokens(&env);
+        if allowed_tokens.contains(&token) {
+            return Err(Error::TokenAlreadyAllowed);
+        }
+        allowed_tokens.push_back(token.clone());
         env.storage()
             .instance()
-            .set(&DataKey::FeePercentage, &fee_percentage);
+            .set(&DataKey::AllowedTokens, &allowed_tokens);
+        env.storage()
+            .instance()
+            .set(&DataKey::TokenFeePercentage(token.clone()), &fee_percentage);
+
+        events::TokenAllowed {
+            token,
+            fee_percentage,
+        }
+        .publish(&env);
 
         Ok(())
     }
 
-    // Fee percentage in basis points (e.g., 200 = 2.00%)
-    pub fn fee_percentage(env: &Env) -> Result<u32, Error> {
+    // Stop new cycles in a token; existing cycles keep paying out in it
+    pub fn disallow_token(env: Env, token: Address) -> Result<(), Error> {
+        Self::require_admin(&env, AdminAction::DisallowToken(token.clone()))?;
+
+        // The web app funds new cycles in the default token and migrate assigns it to old ones
+        if Self::usdc_token(&env)? == token {
+            return Err(Error::DefaultTokenNotRemovable);
+        }
+
+        let mut allowed_tokens = Self::allowed_tokens(&env);
+        let index = allowed_tokens
+            .first_index_of(&token)
+            .ok_or(Error::TokenNotAllowed)?;
+        allowed_tokens.remove(index);
         env.storage()
             .instance()
-            .get(&DataKey::FeePercentage)
-            .ok_or(Error::FeePercentageNotSet)
-    }
+            .set(&DataKey::AllowedTokens, &allowed_tokens);
+        env.storage()
+            .instance()
+            .remove(&DataKey::TokenFeePercentage(token.clone()));
 
-    // Cycle Management
+        events::TokenDisallowed { token }.publish(&env);
 
-    pub fn create_cycle(
-        env: Env,
-        user: Address,
-        duration_months: u32,
-        amount: i128,
-    ) -> Result<u64, Error> {
-        user.require_auth();
+        Ok(())
+    }
 
-        if duration_months < 1 || duration_months > 12 {
-            return Err(Error::InvalidCycleDuration);
-        }
-        if amount <= 0 {
-            return Err(Error::InsufficientFunds);
-        }
+    pub fn allowed_tokens(env: &Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::AllowedTokens)
+            .unwrap_or(Vec::new(env))
+    }
 
-        let fee_percentage = Self::fee_percentage(&env)?;
+    // Fee percentage for cycles in this token, in basis points (e.g., 200 = 2.00%)
+    pub fn fee_percentage(env: &Env, token: Address) -> Result<u32, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::TokenFeePercentage(token))
+            .ok_or(Error::TokenNotAllowed)
+    }
 
-        let operating_fee = Self::calculate_fee(amount, fee_percentage);
-        let current_time = env.ledger().timestamp();
-        let duration_seconds = duration_months as u64 * 30 * 24 * 60 * 60; // ~30 days per month
-        let end_date = current_time + duration_seconds;
+    // Days before and after a due date during which a payment counts as on time
+    pub fn set_payment_window(env: Env, days_before: u32, days_after: u32) -> Result<(), Error> {
+        Self::require_admin(&env, AdminAction::SetPaymentWindow(days_before, days_after))?;
 
-        let cycle_id = Self::next_cycle_id(&env);
+        // Paying earlier than the minimum lead time would defeat the lock
+        if days_before >= MIN_LEAD_TIME_DAYS || days_after > MAX_PAYMENT_GRACE_DAYS {
+            return Err(Error::InvalidPaymentWindow);
+        }
 
-        // Clone user for keys and events
-        let user_for_keys = user.clone();
-        let user_for_event = user.clone();
-        let cycle = BillCycle {
-            user, // Move original user into struct
-            start_date: current_time,
-            end_date,
-            total_deposited: amount,
-            operating_fee,
-            fee_percentage,
-            is_active: true,
-            // last_adjustment_month: Self::get_current_month(&env),
-            last_adjustment_month: 0,
+        let window = PaymentWindow {
+            days_before,
+            days_after,
         };
+        env.storage()
+            .instance()
+            .set(&DataKey::PaymentWindow, &window);
 
-        let cycle_key = DataKey::Cycle(cycle_id);
-        env.storage().persistent().set(&cycle_key, &cycle);
-        Self::extend_ttl(&env, &cycle_key);
+        events::PaymentWindowUpdated {
+            days_before,
+            days_after,
+        }
+        .publish(&env);
 
-        let user_cycles_key = DataKey::UserCycles(user_for_keys.clone());
-        let mut user_cycles: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&user_cycles_key)
-            .unwrap_or(Vec::new(&env));
-        user_cycles.push_back(cycle_id);
+        Ok(())
+    }
+
+    pub fn payment_window(env: &Env) -> PaymentWindow {
         env.storage()
-            .persistent()
-            .set(&user_cycles_key, &user_cycles);
-        Self::extend_ttl(&env, &user_cycles_key);
+            .instance()
+            .get(&DataKey::PaymentWindow)
+            .unwrap_or(PaymentWindow {
+                days_before: 0,
+                days_after: DEFAULT_PAYMENT_GRACE_DAYS,
+            })
+    }
 
-        let all_cycles_key = DataKey::AllCycles;
-        let mut all_cycles: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&all_cycles_key)
-            .unwrap_or(Vec::new(&env));
-        all_cycles.push_back(cycle_id);
-        env.storage().persistent().set(&all_cycles_key, &all_cycles);
-        Self::extend_ttl(&env, &all_cycles_key);
+    // Tip paid per execute_due_bill, funded by keeping fee_share_bps of each operating fee in the vault
+    pub fn set_keeper_tip(
+        env: Env,
+        caller: Address,
+        tip: i128,
+        fee_share_bps: u32,
+    ) -> Result<(), Error> {
+        Self::require_role(
+            &env,
+            &caller,
+            Role::FeeManager,
+            AdminAction::SetKeeperTip(tip, fee_share_bps),
+        )?;
+
+        if tip < 0 || fee_share_bps > 10000 {
+            return Err(Error::InvalidKeeperTip);
+        }
 
-        let cycle_bills_key = DataKey::CycleBills(cycle_id);
-        let empty_bills: Vec<u64> = Vec::new(&env);
+        let keeper_tip = KeeperTip { tip, fee_share_bps };
         env.storage()
-            .persistent()
-            .set(&cycle_bills_key, &empty_bills);
-        Self::extend_ttl(&env, &cycle_bills_key);
+            .instance()
+            .set(&DataKey::KeeperTip, &keeper_tip);
 
-        let usdc_token = Self::usdc_token(&env)?;
-        let token_client = token::TokenClient::new(&env, &usdc_token);
-        token_client.transfer(&user_for_keys, &env.current_contract_address(), &amount);
+        events::KeeperTipUpdated { tip, fee_share_bps }.publish(&env);
 
-        let fee_recipient = Self::fee_recipient(&env)?;
-        token_client.transfer(
-            &env.current_contract_address(),
-            &fee_recipient,
-            &operating_fee,
-        );
+        Ok(())
+    }
 
-        events::CycleCreated {
-            cycle_id,
-            user: user_for_event,
-        }
-        .publish(&env);
+    pub fn keeper_tip(env: &Env) -> KeeperTip {
+        env.storage()
+            .instance()
+            .get(&DataKey::KeeperTip)
+            .unwrap_or(KeeperTip {
+                tip: 0,
+                fee_share_bps: 0,
+            })
+    }
 
-        Ok(cycle_id)
+    // Penalty charged by withdraw_early, in basis points of the withdrawn amount
+    // Changed through the config timelock so a raise can't front-run a pending withdrawal
+    pub fn early_withdrawal_penalty(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::EarlyWithdrawalPenalty)
+            .unwrap_or(DEFAULT_EARLY_WITHDRAWAL_PENALTY_BPS)
     }
 
-    pub fn get_cycle(env: Env, cycle_id: u64) -> Result<BillCycle, Error> {
-        let cycle_key = DataKey::Cycle(cycle_id);
-        let cycle: BillCycle = env
-            .storage()
-            .persistent()
-            .get(&cycle_key)
-            .ok_or(Error::CycleNotFound)?;
+    // Pause one group of entry points, admin only
+    pub fn pause(env: Env, scope: PauseScope) -> Result<(), Error> {
+        Self::require_admin(&env, AdminAction::Pause(scope.clone()))?;
 
-        cycle.user.require_auth();
+        env.storage()
+            .instance()
+            .set(&DataKey::Paused(scope.clone()), &true);
 
-        Self::extend_ttl(&env, &cycle_key);
-        Ok(cycle)
+        events::Paused { scope }.publish(&env);
+
+        Ok(())
     }
 
-    pub fn get_user_cycles(env: Env, user: Address) -> Vec<u64> {
-        user.require_auth();
+    pub fn unpause(env: Env, scope: PauseScope) -> Result<(), Error> {
+        Self::require_admin(&env, AdminAction::Unpause(scope.clone()))?;
 
-        let user_cycles_key = DataKey::UserCycles(user);
-        Self::extend_ttl(&env, &user_cycles_key);
         env.storage()
-            .persistent()
-            .get(&user_cycles_key)
-            .unwrap_or(Vec::new(&env))
+            .instance()
+            .remove(&DataKey::Paused(scope.clone()));
+
+        events::Unpaused { scope }.publish(&env);
+
+        Ok(())
     }
 
-    // Admin-only
-    pub fn get_all_cycles(env: Env) -> Result<Vec<u64>, Error> {
-        Self::require_admin(&env)?;
+    pub fn is_paused(env: &Env, scope: PauseScope) -> bool {
+        env.storage().instance().has(&DataKey::Paused(scope))
+    }
 
-        let all_cycles_key = DataKey::AllCycles;
+    // Solvency
 
-        if env.storage().persistent().has(&all_cycles_key) {
-            Self::extend_ttl(&env, &all_cycles_key);
+    // Compare the vault's balance of each token with what active cycles still hold in it
+    // Anyone can call it; a SolvencyAlert is emitted for every token that is short
+    pub fn check_solvency(env: Env) -> Vec<SolvencyReport> {
+        let mut reports = Vec::new(&env);
+        for token in Self::liability_tokens(&env).iter() {
+            let liabilities: i128 = env
+                .storage()
+                .instance()
+                .get(&DataKey::Liabilities(token.clone()))
+                .unwrap_or(0);
+            let balance =
+                token::TokenClient::new(&env, &token).balance(&env.current_contract_address());
+            let shortfall = (liabilities - balance).max(0);
+
+            if shortfall > 0 {
+                events::SolvencyAlert {
+                    token: token.clone(),
+                    balance,
+                    liabilities,
+                    shortfall,
+                }
+                .publish(&env);
+            }
+
+            reports.push_back(SolvencyReport {
+                token,
+                balance,
+                liabilities,
+                shortfall,
+            });
         }
+        reports
+    }
 
-        Ok(env
-            .storage()
-            .persistent()
-            .get(&all_cycles_key)
-            .unwrap_or(Vec::new(&env)))
+    // Upgrades
+
+    // Written by the constructor; the first release had no upgrade, so it is always set
+    pub fn version(env: &Env) -> u32 {
+        env.storage().instance().get(&DataKey::Version).unwrap()
     }
 
-    /// Anyone can end a cycle after the end_date has passed
-    pub fn end_cycle(env: Env, cycle_id: u64) -> Result<(), Error> {
-        let _guard = ReentrancyGuard::new(&env)?;
+    // Replace the contract code, admin only
+    // Pause every scope first, then call migrate until it returns true
+    // Owners can still emergency withdraw from the cycles each call has migrated
+    pub fn upgrade(env: Env, new_wasm_hash: BytesN<32>) -> Result<(), Error> {
+        Self::require_admin(&env, AdminAction::Upgrade(new_wasm_hash.clone()))?;
 
-        let cycle_key = DataKey::Cycle(cycle_id);
-        let cycle: BillCycle = env
-            .storage()
-            .persistent()
-            .get(&cycle_key)
-            .ok_or(Error::CycleNotFound)?;
+        env.deployer()
+            .update_current_contract_wasm(new_wasm_hash.clone());
 
-        // Check that cycle has ended
-        let current_time = env.ledger().timestamp();
-        if current_time < cycle.end_date {
-            return Err(Error::CycleNotEnded);
-        }
+        events::ContractUpgraded { new_wasm_hash }.publish(&env);
 
-        Self::end_cycle_internal(&env, cycle_id, cycle, cycle_key)
+        Ok(())
     }
 
-    /// Admin can end a cycle at any time
-    pub fn admin_end_cycle(env: Env, cycle_id: u64) -> Result<(), Error> {
-        Self::require_admin(&env)?;
-        let _guard = ReentrancyGuard::new(&env)?;
+    // Rewrite cycles and bills stored in an older layout, at most `limit` cycles per call
+    // Returns true once every cycle is done and the stored version has been bumped
+    pub fn migrate(env: Env, limit: u32) -> Result<bool, Error> {
+        Self::require_admin(&env, AdminAction::Migrate(limit))?;
 
-        let cycle_key = DataKey::Cycle(cycle_id);
-        let cycle: BillCycle = env
+        if Self::version(&env) >= CONTRACT_VERSION {
+            return Err(Error::AlreadyMigrated);
+        }
+
+        let all_cycles: Vec<u64> = env
             .storage()
             .persistent()
-            .get(&cycle_key)
-            .ok_or(Error::CycleNotFound)?;
-
-        Self::end_cycle_internal(&env, cycle_id, cycle, cycle_key)
-    }
+            .get(&DataKey::AllCycles)
+            .unwrap_or(Vec::new(&env));
 
-    // Bill Management
-    // Add one or more bills in a single transaction
-    pub fn add_bills(
-        env: Env,
-        cycle_id: u64,
-        bills: Vec<(String, i128, u64, bool, Vec<u32>, BillCategory)>,
-    ) -> Result<Vec<u64>, Error> {
-        let cycle_key = DataKey::Cycle(cycle_id);
-        let cycle: BillCycle = env
+        let start: u32 = env
             .storage()
-            .persistent()
-            .get(&cycle_key)
-            .ok_or(Error::CycleNotFound)?;
+            .instance()
+            .get(&DataKey::MigrationCursor)
+            .unwrap_or(0);
+        let end = start.saturating_add(limit).min(all_cycles.len());
 
-        cycle.user.require_auth();
+        // Cycles from before tokens were pinned were funded in the current global token
+        let token = Self::usdc_token(&env)?;
 
-        if !cycle.is_active {
-            return Err(Error::CycleNotActive);
+        // Version 3 -> 4: the global fee becomes the fee of the first allowed token
+        if !env.storage().instance().has(&DataKey::AllowedTokens) {
+            let fee_percentage: u32 = env
+                .storage()
+                .instance()
+                .get(&DataKey::FeePercentage)
+                .unwrap_or(200);
+            env.storage().instance().set(
+                &DataKey::AllowedTokens,
+                &Vec::from_array(&env, [token.clone()]),
+            );
+            env.storage()
+                .instance()
+                .set(&DataKey::TokenFeePercentage(token.clone()), &fee_percentage);
+            env.storage().instance().remove(&DataKey::FeePercentage);
         }
 
-        let mut bill_ids = Vec::new(&env);
-        let cycle_bills_key = DataKey::CycleBills(cycle_id);
-        let mut cycle_bills: Vec<u64> = env
+        // Version 4 -> 5: the single admin becomes the first signer, threshold 1
+        if let Some(admin) = env
             .storage()
-            .persistent()
-            .get(&cycle_bills_key)
-            .unwrap_or(Vec::new(&env));
+            .instance()
+            .get::<DataKey, Address>(&DataKey::Admin)
+        {
+            env.storage()
+                .instance()
+                .set(&DataKey::AdminSigners, &Vec::from_array(&env, [admin]));
+            env.storage()
+                .instance()
+                .set(&DataKey::AdminThreshold, &1u32);
+            env.storage().instance().remove(&DataKey::Admin);
+            env.storage().instance().remove(&DataKey::PendingAdmin);
+            env.storage().instance().remove(&DataKey::TransferExpiry);
+        }
 
-        for (name, amount, due_date, is_recurring, recurrence_calendar, category) in bills.iter() {
-            if amount <= 0 {
-                return Err(Error::InvalidBillAmount);
+        // Version 6 -> 7: liabilities are tracked per token instead of being recomputed,
+        // so they are rebuilt as the cycles are reached; later versions already track them
+        let rebuild_liabilities = Self::version(&env) < 7;
+        if rebuild_liabilities && start == 0 {
+            for token in Self::liability_tokens(&env).iter() {
+                env.storage()
+                    .instance()
+                    .remove(&DataKey::Liabilities(token));
             }
+            env.storage().instance().remove(&DataKey::LiabilityTokens);
+        }
 
-            if due_date < cycle.start_date || due_date > cycle.end_date {
-                return Err(Error::InvalidDueDate);
+        for i in start..end {
+            let cycle_id = all_cycles.get_unchecked(i);
+            Self::migrate_cycle(&env, cycle_id, &token);
+            Self::migrate_cycle_bills(&env, cycle_id);
+            if !rebuild_liabilities {
+                continue;
             }
-
-            Self::validate_day_of_month(due_date)?;
-
-            Self::validate_lead_time(&env, due_date)?;
-
-            if is_recurring {
-                for month in recurrence_calendar.iter() {
-                    if month < 1 || month > 12 {
-                        return Err(Error::InvalidRecurrence);
-                    }
-                }
+            if let Some(cycle) = env
+                .storage()
+                .persistent()
+                .get::<DataKey, BillCycle>(&DataKey::Cycle(cycle_id))
+            {
+                Self::adjust_liabilities(&env, &cycle.token, Self::cycle_liability(&cycle));
             }
+        }
 
-            Self::validate_allocation(&env, cycle_id, &cycle, amount, is_recurring)?;
+        if end < all_cycles.len() {
+            env.storage()
+                .instance()
+                .set(&DataKey::MigrationCursor, &end);
+            return Ok(false);
+        }
 
-            let bill_id = Self::next_bill_id(&env);
-            let bill = Bill {
-                id: bill_id,
-                cycle_id,
-                name,
-                amount,
-                due_date,
-                is_paid: false,
-                is_recurring,
-                recurrence_calendar,
-                last_paid_date: None,
-                category,
-            };
+        env.storage().instance().remove(&DataKey::MigrationCursor);
+        env.storage()
+            .instance()
+            .set(&DataKey::Version, &CONTRACT_VERSION);
 
-            let bill_key = DataKey::Bill(bill_id);
-            env.storage().persistent().set(&bill_key, &bill);
-            Self::extend_ttl(&env, &bill_key);
+        events::StorageMigrated {
+            version: CONTRACT_VERSION,
+        }
+        .publish(&env);
 
-            cycle_bills.push_back(bill_id);
-            bill_ids.push_back(bill_id);
+        Ok(true)
+    }
 
-            events::BillAdded { bill_id, cycle_id }.publish(&env);
-        }
+    // Cycle Management
 
-        env.storage()
-            .persistent()
-            .set(&cycle_bills_key, &cycle_bills);
-        Self::extend_ttl(&env, &cycle_bills_key);
+    pub fn create_cycle(
+        env: Env,
+        user: Address,
+        token: Address,
+        duration_months: u32,
+        amount: i128,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env, PauseScope::Deposits)?;
+        user.require_auth();
 
-        Ok(bill_ids)
+        Self::open_cycle(
+            &env,
+            user,
+            token,
+            duration_months,
+            amount,
+            amount,
+            None,
+            false,
+        )
     }
 
-    pub fn get_bill(env: Env, bill_id: u64) -> Result<Bill, Error> {
-        let bill_key = DataKey::Bill(bill_id);
-        let bill: Bill = env
-            .storage()
-            .persistent()
-            .get(&bill_key)
-            .ok_or(Error::BillNotFound)?;
-
-        let cycle_key = DataKey::Cycle(bill.cycle_id);
-        let cycle: BillCycle = env
+    // Owner opts in to renew_cycle running automatically when end_cycle is called
+    pub fn set_auto_renew(env: Env, cycle_id: u64, enabled: bool) -> Result<(), Error> {
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let mut cycle: BillCycle = env
             .storage()
             .persistent()
             .get(&cycle_key)
@@ -481,38 +771,32 @@ impl LockedIn {
 
         cycle.user.require_auth();
 
-        Self::extend_ttl(&env, &bill_key);
-        Ok(bill)
-    }
-
-    pub fn get_cycle_bills(env: Env, cycle_id: u64) -> Vec<u64> {
-        let cycle_key = DataKey::Cycle(cycle_id);
-        if let Some(cycle) = env.storage().persistent().get::<DataKey, BillCycle>(&cycle_key) {
-            cycle.user.require_auth();
-            Self::extend_ttl(&env, &cycle_key);
+        if !cycle.is_active {
+            return Err(Error::CycleNotActive);
         }
 
-        let cycle_bills_key = DataKey::CycleBills(cycle_id);
-        Self::extend_ttl(&env, &cycle_bills_key);
-        env.storage()
-            .persistent()
-            .get(&cycle_bills_key)
-            .unwrap_or(Vec::new(&env))
+        cycle.auto_renew = enabled;
+        env.storage().persistent().set(&cycle_key, &cycle);
+        Self::extend_ttl(&env, &cycle_key);
+
+        events::AutoRenewUpdated { cycle_id, enabled }.publish(&env);
+
+        Ok(())
     }
 
-    // Sends funds back to user's wallet
-    // User can call ONLY on exact due date (same calendar day)
-    pub fn pay_bill(env: Env, bill_id: u64) -> Result<(), Error> {
+    // End a cycle past its end date and roll its surplus, plus extra_deposit, into a new one
+    // Recurring bills are copied over with due dates moved into the new cycle
+    pub fn renew_cycle(
+        env: Env,
+        cycle_id: u64,
+        duration_months: u32,
+        extra_deposit: i128,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env, PauseScope::CycleEnding)?;
+        Self::require_not_paused(&env, PauseScope::Deposits)?;
         let _guard = ReentrancyGuard::new(&env)?;
 
-        let bill_key = DataKey::Bill(bill_id);
-        let mut bill: Bill = env
-            .storage()
-            .persistent()
-            .get(&bill_key)
-            .ok_or(Error::BillNotFound)?;
-
-        let cycle_key = DataKey::Cycle(bill.cycle_id);
+        let cycle_key = DataKey::Cycle(cycle_id);
         let cycle: BillCycle = env
             .storage()
             .persistent()
@@ -521,142 +805,587 @@ impl LockedIn {
 
         cycle.user.require_auth();
 
-        if bill.is_paid {
-            return Err(Error::BillAlreadyPaid);
+        if env.ledger().timestamp() < cycle.end_date {
+            return Err(Error::CycleNotEnded);
         }
-
-        if !cycle.is_active {
-            return Err(Error::CycleNotActive);
+        if extra_deposit < 0 {
+            return Err(Error::InsufficientFunds);
         }
 
-        let current_time = env.ledger().timestamp();
-        let bill_due_day_start = (bill.due_date / 86400) * 86400;
-        let current_day_start = (current_time / 86400) * 86400;
-
-        if current_day_start != bill_due_day_start {
-            return Err(Error::BillNotDueYet);
-        }
+        Self::renew_cycle_internal(
+            &env,
+            cycle_id,
+            cycle,
+            cycle_key,
+            duration_months,
+            extra_deposit,
+        )
+    }
 
-        if bill.is_recurring {
-            if let Some(last_paid) = bill.last_paid_date {
-                const SECONDS_IN_MONTH: u64 = 30 * 86400;
-                if current_time - last_paid < SECONDS_IN_MONTH {
-                    return Err(Error::BillAlreadyPaid);
-                }
-            }
-        }
+    // Top up an active cycle with additional funds
+    // The operating fee is charged on the new deposit at the cycle's fee rate
+    pub fn deposit_to_cycle(env: Env, cycle_id: u64, amount: i128) -> Result<(), Error> {
+        Self::require_not_paused(&env, PauseScope::Deposits)?;
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let mut cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
 
-        bill.last_paid_date = Some(current_time);
+        cycle.user.require_auth();
 
-        if bill.is_recurring {
-            const SECONDS_IN_DAY: u64 = 86400;
-            const AVERAGE_DAYS_IN_MONTH: u64 = 30;
+        if !cycle.is_active {
+            return Err(Error::CycleNotActive);
+        }
+        if amount <= 0 {
+            return Err(Error::InsufficientFunds);
+        }
 
-            let next_due_date = bill.due_date + (AVERAGE_DAYS_IN_MONTH * SECONDS_IN_DAY);
+        let operating_fee = Self::calculate_fee(amount, cycle.fee_percentage);
+        let keeper_budget = Self::calculate_keeper_budget(&env, operating_fee);
 
-            if next_due_date < cycle.end_date {
-                bill.due_date = next_due_date;
-                bill.is_paid = false;
-            } else {
-                bill.is_paid = true;
-            }
-        } else {
-            bill.is_paid = true;
-        }
+        cycle.total_deposited += amount;
+        cycle.operating_fee += operating_fee;
+        cycle.keeper_budget += keeper_budget;
+        env.storage().persistent().set(&cycle_key, &cycle);
+        Self::extend_ttl(&env, &cycle_key);
+        Self::adjust_liabilities(&env, &cycle.token, amount - operating_fee + keeper_budget);
 
-        env.storage().persistent().set(&bill_key, &bill);
-        Self::extend_ttl(&env, &bill_key);
+        let token_client = token::TokenClient::new(&env, &cycle.token);
+        token_client.transfer(&cycle.user, env.current_contract_address(), &amount);
 
-        let usdc_token = Self::usdc_token(&env)?;
-        let token_client = token::TokenClient::new(&env, &usdc_token);
-        token_client.transfer(&env.current_contract_address(), &cycle.user, &bill.amount);
+        let fee_recipient = Self::fee_recipient(&env)?;
+        token_client.transfer(
+            &env.current_contract_address(),
+            &fee_recipient,
+            &(operating_fee - keeper_budget),
+        );
 
-        events::BillPaid {
-            bill_id,
-            amount: bill.amount,
+        events::CycleDeposit {
+            cycle_id,
+            amount,
+            operating_fee,
         }
         .publish(&env);
 
         Ok(())
     }
 
-
-    pub fn admin_pay_bill(env: Env, bill_id: u64) -> Result<(), Error> {
-        Self::require_admin(&env)?;
+    // Owner escape hatch while any part of the contract is paused
+    // Returns the cycle's unallocated funds; money reserved for bills stays in the vault
+    // During a migration it works on cycles migrate has already reached
+    pub fn emergency_withdraw_own_cycle(env: Env, cycle_id: u64) -> Result<i128, Error> {
+        Self::require_cycle_migrated(&env, cycle_id)?;
         let _guard = ReentrancyGuard::new(&env)?;
 
-        let bill_key = DataKey::Bill(bill_id);
-        let mut bill: Bill = env
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let mut cycle: BillCycle = env
             .storage()
             .persistent()
-            .get(&bill_key)
-            .ok_or(Error::BillNotFound)?;
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
 
-        if bill.is_paid {
-            return Err(Error::BillAlreadyPaid);
+        cycle.user.require_auth();
+
+        if !Self::is_paused(&env, PauseScope::Deposits)
+            && !Self::is_paused(&env, PauseScope::Payments)
+            && !Self::is_paused(&env, PauseScope::CycleEnding)
+        {
+            return Err(Error::NotPaused);
         }
 
-        let cycle_key = DataKey::Cycle(bill.cycle_id);
-        let cycle: BillCycle = env
+        if !cycle.is_active {
+            return Err(Error::CycleNotActive);
+        }
+
+        let allocated = Self::calculate_total_allocation(&env, cycle_id, &cycle)?;
+        let unallocated = Self::available_funds(&cycle) - allocated;
+        if unallocated <= 0 {
+            return Err(Error::InsufficientFunds);
+        }
+
+        cycle.total_deposited -= unallocated;
+        env.storage().persistent().set(&cycle_key, &cycle);
+        Self::extend_ttl(&env, &cycle_key);
+        Self::adjust_liabilities(&env, &cycle.token, -unallocated);
+
+        let token_client = token::TokenClient::new(&env, &cycle.token);
+        token_client.transfer(&env.current_contract_address(), &cycle.user, &unallocated);
+
+        events::EmergencyWithdrawal {
+            cycle_id,
+            amount: unallocated,
+        }
+        .publish(&env);
+
+        Ok(unallocated)
+    }
+
+    // Owner takes unallocated funds out of an active cycle before its end date
+    // The penalty goes to the fee recipient; returns the amount the owner received
+    pub fn withdraw_early(env: Env, cycle_id: u64, amount: i128) -> Result<i128, Error> {
+        Self::require_not_paused(&env, PauseScope::CycleEnding)?;
+        let _guard = ReentrancyGuard::new(&env)?;
+
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let mut cycle: BillCycle = env
             .storage()
             .persistent()
             .get(&cycle_key)
             .ok_or(Error::CycleNotFound)?;
 
+        cycle.user.require_auth();
+
         if !cycle.is_active {
             return Err(Error::CycleNotActive);
         }
+        if amount <= 0 {
+            return Err(Error::InsufficientFunds);
+        }
 
-        if bill.is_recurring {
-            if let Some(last_paid) = bill.last_paid_date {
-                let current_time = env.ledger().timestamp();
-                const SECONDS_IN_MONTH: u64 = 30 * 86400;
-                if current_time - last_paid < SECONDS_IN_MONTH {
-                    return Err(Error::BillAlreadyPaid);
-                }
-            }
+        // Money reserved for bills can only leave through payments
+        let allocated = Self::calculate_total_allocation(&env, cycle_id, &cycle)?;
+        let unallocated = Self::available_funds(&cycle) - allocated;
+        if amount > unallocated {
+            return Err(Error::EarlyWithdrawalNotAllowed);
         }
 
-        let current_time = env.ledger().timestamp();
-        bill.last_paid_date = Some(current_time);
+        let penalty = Self::calculate_fee(amount, Self::early_withdrawal_penalty(&env));
+        let payout = amount - penalty;
 
-        if bill.is_recurring {
-            const SECONDS_IN_DAY: u64 = 86400;
-            const AVERAGE_DAYS_IN_MONTH: u64 = 30;
+        cycle.total_deposited -= amount;
+        env.storage().persistent().set(&cycle_key, &cycle);
+        Self::extend_ttl(&env, &cycle_key);
+        Self::adjust_liabilities(&env, &cycle.token, -amount);
 
-            let next_due_date = bill.due_date + (AVERAGE_DAYS_IN_MONTH * SECONDS_IN_DAY);
+        let token_client = token::TokenClient::new(&env, &cycle.token);
+        token_client.transfer(&env.current_contract_address(), &cycle.user, &payout);
+        if penalty > 0 {
+            let fee_recipient = Self::fee_recipient(&env)?;
+            token_client.transfer(&env.current_contract_address(), &fee_recipient, &penalty);
+        }
 
-            if next_due_date < cycle.end_date {
-                bill.due_date = next_due_date;
-                bill.is_paid = false;
-            } else {
-                bill.is_paid = true;
-            }
-        } else {
-            bill.is_paid = true;
+        events::EarlyWithdrawal {
+            cycle_id,
+            amount: payout,
+            penalty,
         }
+        .publish(&env);
 
-        env.storage().persistent().set(&bill_key, &bill);
-        Self::extend_ttl(&env, &bill_key);
+        Ok(payout)
+    }
+
+    // Funding and bill figures for one cycle, owner only
+    // Uses the same allocation as validate_allocation, so free_balance is what new bills can reserve
+    pub fn get_cycle_summary(env: Env, cycle_id: u64) -> Result<CycleSummary, Error> {
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+        Self::extend_ttl(&env, &cycle_key);
 
-        let usdc_token = Self::usdc_token(&env)?;
-        let token_client = token::TokenClient::new(&env, &usdc_token);
-        token_client.transfer(&env.current_contract_address(), &cycle.user, &bill.amount);
+        let allocation = Self::calculate_total_allocation(&env, cycle_id, &cycle)?;
 
-        events::BillPaid {
-            bill_id,
-            amount: bill.amount,
+        let bill_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::CycleBills(cycle_id))
+            .unwrap_or(Vec::new(&env));
+
+        // Same lateness rule as pay_bill_internal
+        let days_after = Self::payment_window(&env).days_after as u64;
+        let current_day_start = (env.ledger().timestamp() / 86400) * 86400;
+
+        let mut next_due_date: Option<u64> = None;
+        let mut paid_bills = 0u32;
+        let mut pending_bills = 0u32;
+        let mut overdue_bills = 0u32;
+        for bill_id in bill_ids.iter() {
+            let bill: Bill = match env.storage().persistent().get(&DataKey::Bill(bill_id)) {
+                Some(bill) => bill,
+                None => continue,
+            };
+            if bill.is_paid {
+                paid_bills += 1;
+                continue;
+            }
+
+            let bill_due_day_start = (bill.due_date / 86400) * 86400;
+            if current_day_start > bill_due_day_start + days_after * 86400 {
+                overdue_bills += 1;
+            } else {
+                pending_bills += 1;
+            }
+
+            if cycle.is_active && next_due_date.is_none_or(|next| bill.due_date < next) {
+                next_due_date = Some(bill.due_date);
+            }
+        }
+
+        Ok(CycleSummary {
+            total_deposited: cycle.total_deposited,
+            operating_fee: cycle.operating_fee,
+            reserved: allocation - cycle.total_disbursed,
+            disbursed: cycle.total_disbursed,
+            free_balance: Self::available_funds(&cycle) - allocation,
+            next_due_date,
+            paid_bills,
+            pending_bills,
+            overdue_bills,
+        })
+    }
+
+    pub fn get_cycle(env: Env, cycle_id: u64) -> Result<BillCycle, Error> {
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+
+        Self::extend_ttl(&env, &cycle_key);
+        Ok(cycle)
+    }
+
+    // Pass a token to only list cycles funded in it
+    pub fn get_user_cycles(env: Env, user: Address, token: Option<Address>) -> Vec<u64> {
+        user.require_auth();
+
+        let user_cycles_key = DataKey::UserCycles(user);
+        Self::extend_ttl(&env, &user_cycles_key);
+        let user_cycles: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&user_cycles_key)
+            .unwrap_or(Vec::new(&env));
+
+        let token = match token {
+            Some(token) => token,
+            None => return user_cycles,
+        };
+
+        let mut filtered = Vec::new(&env);
+        for cycle_id in user_cycles.iter() {
+            let cycle: Option<BillCycle> =
+                env.storage().persistent().get(&DataKey::Cycle(cycle_id));
+            if cycle.is_some_and(|cycle| cycle.token == token) {
+                filtered.push_back(cycle_id);
+            }
+        }
+        filtered
+    }
+
+    // Keepers and admin signers only; reading needs no approvals
+    pub fn get_all_cycles(env: Env, caller: Address) -> Result<Vec<u64>, Error> {
+        caller.require_auth();
+        if !Self::has_role(env.clone(), Role::Keeper, caller.clone())
+            && !Self::admin_signers(env.clone()).contains(&caller)
+        {
+            return Err(Error::Unauthorized);
         }
-        .publish(&env);
 
+        let all_cycles_key = DataKey::AllCycles;
+
+        if env.storage().persistent().has(&all_cycles_key) {
+            Self::extend_ttl(&env, &all_cycles_key);
+        }
+
+        Ok(env
+            .storage()
+            .persistent()
+            .get(&all_cycles_key)
+            .unwrap_or(Vec::new(&env)))
+    }
+
+    /// Anyone can end a cycle after the end_date has passed
+    /// Cycles with auto_renew set are renewed for the same duration when possible
+    pub fn end_cycle(env: Env, cycle_id: u64) -> Result<(), Error> {
+        Self::require_not_paused(&env, PauseScope::CycleEnding)?;
+        let _guard = ReentrancyGuard::new(&env)?;
+
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        // Check that cycle has ended
+        let current_time = env.ledger().timestamp();
+        if current_time < cycle.end_date {
+            return Err(Error::CycleNotEnded);
+        }
+
+        // A renewal that can't go ahead fails before writing anything, so fall back to a plain end
+        if cycle.auto_renew && cycle.is_active && !Self::is_paused(&env, PauseScope::Deposits) {
+            let duration_months = Self::months_between(cycle.start_date, cycle.end_date);
+            let renewed = Self::renew_cycle_internal(
+                &env,
+                cycle_id,
+                cycle.clone(),
+                cycle_key.clone(),
+                duration_months,
+                0,
+            );
+            if renewed.is_ok() {
+                return Ok(());
+            }
+        }
+
+        Self::end_cycle_internal(&env, cycle_id, cycle, cycle_key, true)?;
         Ok(())
     }
 
-    // Cancel a single occurrence of a bill
-    // For recurring bills it skips next occurrence by advancing last_paid_date by 30 days
-    // For non-recurring bills it deletes the bill entirely
-    /// Skip the current month's payment for a recurring bill
-    pub fn skip_bill(env: Env, bill_id: u64) -> Result<(), Error> {
+    /// Admin can end a cycle at any time
+    pub fn admin_end_cycle(env: Env, cycle_id: u64) -> Result<(), Error> {
+        Self::require_not_paused(&env, PauseScope::CycleEnding)?;
+        Self::require_admin(&env, AdminAction::EndCycle(cycle_id))?;
+        let _guard = ReentrancyGuard::new(&env)?;
+
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        Self::end_cycle_internal(&env, cycle_id, cycle, cycle_key, true)?;
+        Ok(())
+    }
+
+    // Bill Management
+    // Add one or more bills in a single transaction
+    pub fn add_bills(env: Env, cycle_id: u64, bills: Vec<BillInput>) -> Result<Vec<u64>, Error> {
+        Self::require_not_paused(&env, PauseScope::Deposits)?;
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+
+        if !cycle.is_active {
+            return Err(Error::CycleNotActive);
+        }
+
+        let mut bill_ids = Vec::new(&env);
+        let cycle_bills_key = DataKey::CycleBills(cycle_id);
+        let mut cycle_bills: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&cycle_bills_key)
+            .unwrap_or(Vec::new(&env));
+
+        // The cycle's bill list is only saved after the loop, so earlier bills in the batch
+        // are counted here
+        let mut batch_allocation: i128 = 0;
+
+        for (name, amount, due_date, is_recurring, recurrence_calendar, category, payee) in
+            bills.iter()
+        {
+            Self::validate_new_bill(&env, &cycle, amount, due_date, &payee)?;
+
+            let due_date = if is_recurring {
+                Self::first_scheduled_due_date(due_date, &recurrence_calendar, cycle.end_date)?
+            } else {
+                due_date
+            };
+
+            let occurrences = Self::count_occurrences(
+                due_date,
+                is_recurring,
+                &recurrence_calendar,
+                cycle.end_date,
+            )?;
+            let bill_cost = amount * occurrences;
+            Self::validate_allocation(&env, cycle_id, &cycle, batch_allocation + bill_cost)?;
+            batch_allocation += bill_cost;
+
+            let bill_id = Self::next_bill_id(&env);
+            let bill = Bill {
+                id: bill_id,
+                cycle_id,
+                name,
+                amount,
+                due_date,
+                is_paid: false,
+                is_recurring,
+                recurrence_calendar,
+                last_paid_date: None,
+                category,
+                payee,
+                is_emergency: false,
+                installment_count: 0,
+                installments_paid: 0,
+                remaining_balance: 0,
+                is_variable: false,
+                actual_amount: None,
+            };
+
+            Self::store_bill(&env, &bill);
+
+            cycle_bills.push_back(bill_id);
+            bill_ids.push_back(bill_id);
+
+            events::BillAdded { bill_id, cycle_id }.publish(&env);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&cycle_bills_key, &cycle_bills);
+        Self::extend_ttl(&env, &cycle_bills_key);
+
+        Ok(bill_ids)
+    }
+
+    // Add a bill paid off in plan.installment_count monthly installments
+    // Each installment is the total divided by the count, rounded up, so the last one may be smaller
+    // The cycle only reserves the installments that fall before its end date
+    pub fn add_installment_bill(
+        env: Env,
+        cycle_id: u64,
+        name: String,
+        plan: InstallmentPlan,
+        due_date: u64,
+        category: BillCategory,
+        payee: Address,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env, PauseScope::Deposits)?;
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+
+        if !cycle.is_active {
+            return Err(Error::CycleNotActive);
+        }
+
+        if plan.installment_count == 0 {
+            return Err(Error::InvalidBillAmount);
+        }
+        Self::validate_new_bill(&env, &cycle, plan.total_amount, due_date, &payee)?;
+
+        let installment_count = plan.installment_count as i128;
+        let installment_amount = (plan.total_amount + installment_count - 1) / installment_count;
+
+        let bill_id = Self::next_bill_id(&env);
+        let bill = Bill {
+            id: bill_id,
+            cycle_id,
+            name,
+            amount: installment_amount,
+            due_date,
+            is_paid: false,
+            is_recurring: true,
+            recurrence_calendar: Vec::new(&env),
+            last_paid_date: None,
+            category,
+            payee,
+            is_emergency: false,
+            installment_count: plan.installment_count,
+            installments_paid: 0,
+            remaining_balance: plan.total_amount,
+            is_variable: false,
+            actual_amount: None,
+        };
+
+        let reservation = Self::bill_reservation(&bill, due_date, cycle.end_date)?;
+        Self::validate_allocation(&env, cycle_id, &cycle, reservation)?;
+
+        Self::store_bill(&env, &bill);
+        Self::push_cycle_bill(&env, cycle_id, bill_id);
+
+        events::BillAdded { bill_id, cycle_id }.publish(&env);
+
+        Ok(bill_id)
+    }
+
+    // Add a recurring bill whose amount changes every occurrence, such as a utility bill
+    // The cycle reserves max_amount per occurrence; set_bill_amount fills in the real figure
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_variable_bill(
+        env: Env,
+        cycle_id: u64,
+        name: String,
+        max_amount: i128,
+        due_date: u64,
+        recurrence_calendar: Vec<u32>,
+        category: BillCategory,
+        payee: Address,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env, PauseScope::Deposits)?;
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+
+        if !cycle.is_active {
+            return Err(Error::CycleNotActive);
+        }
+
+        Self::validate_new_bill(&env, &cycle, max_amount, due_date, &payee)?;
+        let due_date =
+            Self::first_scheduled_due_date(due_date, &recurrence_calendar, cycle.end_date)?;
+
+        let bill_id = Self::next_bill_id(&env);
+        let bill = Bill {
+            id: bill_id,
+            cycle_id,
+            name,
+            amount: max_amount,
+            due_date,
+            is_paid: false,
+            is_recurring: true,
+            recurrence_calendar,
+            last_paid_date: None,
+            category,
+            payee,
+            is_emergency: false,
+            installment_count: 0,
+            installments_paid: 0,
+            remaining_balance: 0,
+            is_variable: true,
+            actual_amount: None,
+        };
+
+        let reservation = Self::bill_reservation(&bill, due_date, cycle.end_date)?;
+        Self::validate_allocation(&env, cycle_id, &cycle, reservation)?;
+
+        Self::store_bill(&env, &bill);
+        Self::push_cycle_bill(&env, cycle_id, bill_id);
+
+        events::BillAdded { bill_id, cycle_id }.publish(&env);
+
+        Ok(bill_id)
+    }
+
+    // Set what the current occurrence of a variable bill actually costs
+    // The cycle owner or the bill's payee can set it, never above the bill's max amount
+    pub fn set_bill_amount(
+        env: Env,
+        caller: Address,
+        bill_id: u64,
+        amount: i128,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+
         let bill_key = DataKey::Bill(bill_id);
         let mut bill: Bill = env
             .storage()
@@ -664,63 +1393,1628 @@ impl LockedIn {
             .get(&bill_key)
             .ok_or(Error::BillNotFound)?;
 
-        let cycle_key = DataKey::Cycle(bill.cycle_id);
-        let mut cycle: BillCycle = env
+        let cycle: BillCycle = env
             .storage()
             .persistent()
-            .get(&cycle_key)
+            .get(&DataKey::Cycle(bill.cycle_id))
             .ok_or(Error::CycleNotFound)?;
 
-        cycle.user.require_auth();
+        if caller != cycle.user && caller != bill.payee {
+            return Err(Error::Unauthorized);
+        }
 
         if !cycle.is_active {
             return Err(Error::CycleNotActive);
         }
 
-        let current_month = Self::get_current_month(&env);
-        if cycle.last_adjustment_month == current_month {
-            return Err(Error::MonthlyAdjustmentLimitReached);
+        if bill.is_paid {
+            return Err(Error::BillAlreadyPaid);
         }
 
-        if bill.is_recurring {
-            let current_time = env.ledger().timestamp();
+        if !bill.is_variable || amount <= 0 || amount > bill.amount {
+            return Err(Error::InvalidBillAmount);
+        }
 
-            // Skip to next month using proper calendar calculation
-            let next_month_timestamp = Self::add_one_month(current_time)?;
-            bill.last_paid_date = Some(next_month_timestamp);
-            env.storage().persistent().set(&bill_key, &bill);
-            Self::extend_ttl(&env, &bill_key);
-        } else {
-            env.storage().persistent().remove(&bill_key);
+        bill.actual_amount = Some(amount);
+        env.storage().persistent().set(&bill_key, &bill);
+        Self::extend_ttl(&env, &bill_key);
+
+        events::BillAmountSet {
+            bill_id,
+            amount,
+            set_by: caller,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    // Add an unplanned bill that can't wait for the usual 7-day lead time
+    // Bypasses the lead time and day-of-month rules, limited by count and amount per cycle
+    pub fn add_emergency_bill(
+        env: Env,
+        cycle_id: u64,
+        name: String,
+        amount: i128,
+        due_date: u64,
+        category: BillCategory,
+        payee: Address,
+    ) -> Result<u64, Error> {
+        Self::require_not_paused(&env, PauseScope::Deposits)?;
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let mut cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+
+        if !cycle.is_active {
+            return Err(Error::CycleNotActive);
+        }
+
+        if amount <= 0 {
+            return Err(Error::InvalidBillAmount);
+        }
+
+        Self::validate_payee(&env, &payee)?;
+
+        // Can be due today, but not in the past
+        let current_day_start = (env.ledger().timestamp() / 86400) * 86400;
+        if due_date < current_day_start || due_date > cycle.end_date {
+            return Err(Error::InvalidDueDate);
+        }
+
+        let available = Self::available_funds(&cycle);
+        let max_emergency_allocation = (available * MAX_EMERGENCY_ALLOCATION_BPS) / 10000;
+        if cycle.emergency_bill_count >= MAX_EMERGENCY_BILLS_PER_CYCLE
+            || cycle.emergency_allocated + amount > max_emergency_allocation
+        {
+            return Err(Error::EmergencyBillLimitExceeded);
+        }
+
+        Self::validate_allocation(&env, cycle_id, &cycle, amount)?;
+
+        let bill_id = Self::next_bill_id(&env);
+        let bill = Bill {
+            id: bill_id,
+            cycle_id,
+            name,
+            amount,
+            due_date,
+            is_paid: false,
+            is_recurring: false,
+            recurrence_calendar: Vec::new(&env),
+            last_paid_date: None,
+            category,
+            payee,
+            is_emergency: true,
+            installment_count: 0,
+            installments_paid: 0,
+            remaining_balance: 0,
+            is_variable: false,
+            actual_amount: None,
+        };
+
+        Self::store_bill(&env, &bill);
+        Self::push_cycle_bill(&env, cycle_id, bill_id);
+
+        cycle.emergency_bill_count += 1;
+        cycle.emergency_allocated += amount;
+        env.storage().persistent().set(&cycle_key, &cycle);
+        Self::extend_ttl(&env, &cycle_key);
+
+        events::EmergencyBillAdded {
+            bill_id,
+            cycle_id,
+            amount,
+        }
+        .publish(&env);
+
+        Ok(bill_id)
+    }
+
+    // Edit a bill in place, keeping its ID
+    // Does not count towards the monthly adjustment limit
+    pub fn update_bill(
+        env: Env,
+        bill_id: u64,
+        name: String,
+        amount: i128,
+        due_date: u64,
+        category: BillCategory,
+    ) -> Result<(), Error> {
+        Self::require_not_paused(&env, PauseScope::Deposits)?;
+        let bill_key = DataKey::Bill(bill_id);
+        let mut bill: Bill = env
+            .storage()
+            .persistent()
+            .get(&bill_key)
+            .ok_or(Error::BillNotFound)?;
+
+        let cycle_key = DataKey::Cycle(bill.cycle_id);
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+
+        if !cycle.is_active {
+            return Err(Error::CycleNotActive);
+        }
+
+        if bill.is_paid {
+            return Err(Error::BillAlreadyPaid);
+        }
+
+        if amount <= 0 {
+            return Err(Error::InvalidBillAmount);
+        }
+
+        if due_date < cycle.start_date || due_date > cycle.end_date {
+            return Err(Error::InvalidDueDate);
+        }
+
+        Self::validate_day_of_month(due_date)?;
+
+        Self::validate_lead_time(&env, due_date)?;
+
+        let mut due_date = due_date;
+        if bill.is_recurring && !Self::is_scheduled_month(&bill.recurrence_calendar, due_date)? {
+            due_date =
+                Self::next_scheduled_due_date(due_date, &bill.recurrence_calendar, cycle.end_date)?
+                    .ok_or(Error::InvalidRecurrence)?;
+        }
+
+        // Only the difference from the bill's current reservation needs new funds
+        let current_reservation = Self::bill_reservation(&bill, bill.due_date, cycle.end_date)?;
+        let mut updated_bill = bill.clone();
+        updated_bill.amount = amount;
+        let new_reservation = Self::bill_reservation(&updated_bill, due_date, cycle.end_date)?;
+        Self::validate_allocation(
+            &env,
+            bill.cycle_id,
+            &cycle,
+            new_reservation - current_reservation,
+        )?;
+
+        let event = events::BillUpdated {
+            bill_id,
+            old_name: bill.name.clone(),
+            new_name: name.clone(),
+            old_amount: bill.amount,
+            new_amount: amount,
+            old_due_date: bill.due_date,
+            new_due_date: due_date,
+            old_category: bill.category.clone(),
+            new_category: category.clone(),
+        };
+
+        if due_date != bill.due_date {
+            Self::unindex_due_bill(&env, bill_id, bill.due_date);
+            Self::index_due_bill(&env, bill_id, due_date);
+            Self::drop_invoice(&env, bill_id);
+        }
+
+        if bill.actual_amount.is_some_and(|actual| actual > amount) {
+            bill.actual_amount = None;
+        }
+        bill.name = name;
+        bill.amount = amount;
+        bill.due_date = due_date;
+        bill.category = category;
+
+        env.storage().persistent().set(&bill_key, &bill);
+        Self::extend_ttl(&env, &bill_key);
+
+        event.publish(&env);
+
+        Ok(())
+    }
+
+    pub fn get_bill(env: Env, bill_id: u64) -> Result<Bill, Error> {
+        let bill_key = DataKey::Bill(bill_id);
+        let bill: Bill = env
+            .storage()
+            .persistent()
+            .get(&bill_key)
+            .ok_or(Error::BillNotFound)?;
+
+        let cycle_key = DataKey::Cycle(bill.cycle_id);
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+
+        Self::extend_ttl(&env, &bill_key);
+        Ok(bill)
+    }
+
+    pub fn get_cycle_bills(env: Env, cycle_id: u64) -> Vec<u64> {
+        let cycle_key = DataKey::Cycle(cycle_id);
+        if let Some(cycle) = env
+            .storage()
+            .persistent()
+            .get::<DataKey, BillCycle>(&cycle_key)
+        {
+            cycle.user.require_auth();
+            Self::extend_ttl(&env, &cycle_key);
+        }
+
+        let cycle_bills_key = DataKey::CycleBills(cycle_id);
+        Self::extend_ttl(&env, &cycle_bills_key);
+        env.storage()
+            .persistent()
+            .get(&cycle_bills_key)
+            .unwrap_or(Vec::new(&env))
+    }
+
+    // Invoices
+
+    // Payee asks to be paid `amount` for the bill's current occurrence
+    // Once the owner approves it, the next payment of the bill settles the invoiced amount;
+    // until the owner approves or rejects it the bill can't be paid
+    pub fn submit_invoice(
+        env: Env,
+        bill_id: u64,
+        amount: i128,
+        reference: String,
+    ) -> Result<(), Error> {
+        let bill: Bill = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Bill(bill_id))
+            .ok_or(Error::BillNotFound)?;
+
+        bill.payee.require_auth();
+
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Cycle(bill.cycle_id))
+            .ok_or(Error::CycleNotFound)?;
+
+        if !cycle.is_active {
+            return Err(Error::CycleNotActive);
+        }
+        if bill.is_paid {
+            return Err(Error::BillAlreadyPaid);
+        }
+
+        let invoice_key = DataKey::Invoice(bill_id);
+        if env.storage().persistent().has(&invoice_key) {
+            return Err(Error::InvoiceAlreadyPending);
+        }
+
+        // Bounded by what the cycle reserves for this occurrence
+        let reserved = if bill.installment_count > 0 {
+            bill.amount.min(bill.remaining_balance)
+        } else {
+            bill.amount
+        };
+        if amount <= 0 || amount > reserved {
+            return Err(Error::InvalidBillAmount);
+        }
+
+        let invoice = Invoice {
+            bill_id,
+            amount,
+            reference: reference.clone(),
+            due_date: bill.due_date,
+            submitted_at: env.ledger().timestamp(),
+            is_approved: false,
+        };
+        env.storage().persistent().set(&invoice_key, &invoice);
+        Self::extend_ttl(&env, &invoice_key);
+
+        events::InvoiceSubmitted {
+            bill_id,
+            payee: bill.payee,
+            amount,
+            reference,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    pub fn approve_invoice(env: Env, bill_id: u64) -> Result<(), Error> {
+        let (mut invoice, invoice_key) = Self::owner_invoice(&env, bill_id)?;
+
+        if invoice.is_approved {
+            return Err(Error::InvoiceAlreadyApproved);
+        }
+
+        invoice.is_approved = true;
+        env.storage().persistent().set(&invoice_key, &invoice);
+        Self::extend_ttl(&env, &invoice_key);
+
+        events::InvoiceApproved {
+            bill_id,
+            amount: invoice.amount,
+        }
+        .publish(&env);
+
+        Ok(())
+    }
+
+    // Drop an invoice, approved or not; the payee can submit a new one
+    pub fn reject_invoice(env: Env, bill_id: u64) -> Result<(), Error> {
+        let (_, invoice_key) = Self::owner_invoice(&env, bill_id)?;
+
+        env.storage().persistent().remove(&invoice_key);
+
+        events::InvoiceRejected { bill_id }.publish(&env);
+
+        Ok(())
+    }
+
+    // Readable by the payee as well as the owner
+    pub fn get_invoice(env: Env, bill_id: u64) -> Result<Invoice, Error> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Invoice(bill_id))
+            .ok_or(Error::InvoiceNotFound)
+    }
+
+    // Invoices waiting on the cycle's bills, approved ones included
+    pub fn get_pending_invoices(env: Env, cycle_id: u64) -> Result<Vec<Invoice>, Error> {
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Cycle(cycle_id))
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+
+        let bill_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::CycleBills(cycle_id))
+            .unwrap_or(Vec::new(&env));
+
+        let mut invoices = Vec::new(&env);
+        for bill_id in bill_ids.iter() {
+            if let Some(invoice) = env.storage().persistent().get(&DataKey::Invoice(bill_id)) {
+                invoices.push_back(invoice);
+            }
+        }
+        Ok(invoices)
+    }
+
+    // Bills due on any UTC day between from_ts and to_ts, in due-date order
+    // Paginate with cursor (entries to skip) and limit; a short page means the end was reached
+    pub fn get_due_bills(
+        env: Env,
+        from_ts: u64,
+        to_ts: u64,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<Vec<u64>, Error> {
+        let from_day = from_ts / 86400;
+        let to_day = to_ts / 86400;
+
+        if to_day < from_day || to_day - from_day > MAX_DUE_BILLS_RANGE_DAYS {
+            return Err(Error::InvalidTimestamp);
+        }
+
+        let mut skipped: u32 = 0;
+        let mut due_bills = Vec::new(&env);
+
+        for day in from_day..=to_day {
+            let due_bills_key = DataKey::DueBills(day);
+            let bill_ids: Vec<u64> = match env.storage().persistent().get(&due_bills_key) {
+                Some(bill_ids) => bill_ids,
+                None => continue,
+            };
+            Self::extend_ttl(&env, &due_bills_key);
+
+            for bill_id in bill_ids.iter() {
+                if due_bills.len() >= limit {
+                    return Ok(due_bills);
+                }
+                if skipped < cursor {
+                    skipped += 1;
+                    continue;
+                }
+                due_bills.push_back(bill_id);
+            }
+        }
+
+        Ok(due_bills)
+    }
+
+    // Sends funds to the bill's payee
+    // User can call from the start of the payment window onwards; overdue bills stay payable
+    pub fn pay_bill(env: Env, bill_id: u64) -> Result<(), Error> {
+        Self::require_not_paused(&env, PauseScope::Payments)?;
+        let _guard = ReentrancyGuard::new(&env)?;
+
+        let bill_key = DataKey::Bill(bill_id);
+        let bill: Bill = env
+            .storage()
+            .persistent()
+            .get(&bill_key)
+            .ok_or(Error::BillNotFound)?;
+
+        let cycle_key = DataKey::Cycle(bill.cycle_id);
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+
+        if bill.is_paid {
+            return Err(Error::BillAlreadyPaid);
+        }
+
+        if !cycle.is_active {
+            return Err(Error::CycleNotActive);
+        }
+
+        Self::pay_bill_internal(&env, bill_id, bill, bill_key, cycle, cycle_key)
+    }
+
+    // Keeper, or an admin signer with the payment approved
+    pub fn admin_pay_bill(env: Env, caller: Address, bill_id: u64) -> Result<(), Error> {
+        Self::require_not_paused(&env, PauseScope::Payments)?;
+        Self::require_role(&env, &caller, Role::Keeper, AdminAction::PayBill(bill_id))?;
+        let _guard = ReentrancyGuard::new(&env)?;
+
+        Self::process_bill_payment(&env, bill_id)
+    }
+
+    // Pay several bills under a single reentrancy guard
+    // A bill that can't be paid is reported in its result instead of reverting the batch
+    pub fn admin_pay_bills(
+        env: Env,
+        caller: Address,
+        bill_ids: Vec<u64>,
+    ) -> Result<Vec<PaymentResult>, Error> {
+        Self::require_not_paused(&env, PauseScope::Payments)?;
+        Self::require_role(
+            &env,
+            &caller,
+            Role::Keeper,
+            AdminAction::PayBills(bill_ids.clone()),
+        )?;
+        let _guard = ReentrancyGuard::new(&env)?;
+
+        Ok(Self::process_bill_payments(&env, bill_ids))
+    }
+
+    // Pay up to `limit` bills from the due-date index for the UTC day containing `day`
+    // Paid bills leave the index but failed ones stay, so pass the failures seen so far as `cursor`
+    pub fn pay_due_bills(
+        env: Env,
+        caller: Address,
+        day: u64,
+        cursor: u32,
+        limit: u32,
+    ) -> Result<Vec<PaymentResult>, Error> {
+        Self::require_not_paused(&env, PauseScope::Payments)?;
+        Self::require_role(
+            &env,
+            &caller,
+            Role::Keeper,
+            AdminAction::PayDueBills(day, cursor, limit),
+        )?;
+        let _guard = ReentrancyGuard::new(&env)?;
+
+        let due_bills_key = DataKey::DueBills(day / 86400);
+        let due_bills: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&due_bills_key)
+            .unwrap_or(Vec::new(&env));
+
+        let start = cursor.min(due_bills.len());
+        let end = start.saturating_add(limit).min(due_bills.len());
+        let bill_ids = due_bills.slice(start..end);
+
+        Ok(Self::process_bill_payments(&env, bill_ids))
+    }
+
+    // Anyone can pay a bill once it's due, under the same rules as pay_bill
+    // The caller is tipped from the cycle's keeper budget
+    pub fn execute_due_bill(env: Env, caller: Address, bill_id: u64) -> Result<i128, Error> {
+        Self::require_not_paused(&env, PauseScope::Payments)?;
+        caller.require_auth();
+        let _guard = ReentrancyGuard::new(&env)?;
+
+        Self::process_bill_payment(&env, bill_id)?;
+
+        let bill: Bill = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Bill(bill_id))
+            .ok_or(Error::BillNotFound)?;
+        let cycle_key = DataKey::Cycle(bill.cycle_id);
+        let mut cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        let tip = Self::keeper_tip(&env).tip.min(cycle.keeper_budget);
+        if tip > 0 {
+            cycle.keeper_budget -= tip;
+            env.storage().persistent().set(&cycle_key, &cycle);
+            Self::extend_ttl(&env, &cycle_key);
+            Self::adjust_liabilities(&env, &cycle.token, -tip);
+
+            let token_client = token::TokenClient::new(&env, &cycle.token);
+            token_client.transfer(&env.current_contract_address(), &caller, &tip);
+        }
+
+        events::KeeperTipPaid {
+            bill_id,
+            keeper: caller,
+            tip,
+        }
+        .publish(&env);
+
+        Ok(tip)
+    }
+
+    // Cancel a single occurrence of a bill
+    // For recurring bills it skips next occurrence by advancing the due date to the next scheduled month
+    // For non-recurring bills it deletes the bill entirely
+    /// Skip the current month's payment for a recurring bill
+    pub fn skip_bill(env: Env, bill_id: u64) -> Result<(), Error> {
+        let bill_key = DataKey::Bill(bill_id);
+        let mut bill: Bill = env
+            .storage()
+            .persistent()
+            .get(&bill_key)
+            .ok_or(Error::BillNotFound)?;
+
+        let cycle_key = DataKey::Cycle(bill.cycle_id);
+        let mut cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+
+        if !cycle.is_active {
+            return Err(Error::CycleNotActive);
+        }
+
+        let current_month = Self::get_current_month(&env);
+        if cycle.last_adjustment_month == current_month {
+            return Err(Error::MonthlyAdjustmentLimitReached);
+        }
+
+        if bill.is_recurring {
+            let current_time = env.ledger().timestamp();
+
+            // Skip to next month using proper calendar calculation
+            let next_month_timestamp = Self::add_months(current_time, 1)?;
+            bill.last_paid_date = Some(next_month_timestamp);
+            Self::skip_occurrence(&env, &mut bill, cycle.end_date)?;
+            env.storage().persistent().set(&bill_key, &bill);
+            Self::extend_ttl(&env, &bill_key);
+        } else {
+            env.storage().persistent().remove(&bill_key);
+            Self::unindex_due_bill(&env, bill_id, bill.due_date);
+            Self::drop_invoice(&env, bill_id);
+
+            let cycle_bills_key = DataKey::CycleBills(bill.cycle_id);
+            let cycle_bills: Vec<u64> = env
+                .storage()
+                .persistent()
+                .get(&cycle_bills_key)
+                .unwrap_or(Vec::new(&env));
+
+            let mut new_bills = Vec::new(&env);
+            for id in cycle_bills.iter() {
+                if id != bill_id {
+                    new_bills.push_back(id);
+                }
+            }
+            env.storage().persistent().set(&cycle_bills_key, &new_bills);
+            Self::extend_ttl(&env, &cycle_bills_key);
+        }
+
+        cycle.last_adjustment_month = current_month;
+        env.storage().persistent().set(&cycle_key, &cycle);
+        Self::extend_ttl(&env, &cycle_key);
+
+        events::BillCancelled { bill_id }.publish(&env);
+
+        Ok(())
+    }
+
+    /// Delete a bill completely (all future occurrences)
+    pub fn delete_bill(env: Env, bill_id: u64) -> Result<(), Error> {
+        let bill_key = DataKey::Bill(bill_id);
+        let bill: Bill = env
+            .storage()
+            .persistent()
+            .get(&bill_key)
+            .ok_or(Error::BillNotFound)?;
+
+        let cycle_key = DataKey::Cycle(bill.cycle_id);
+        let mut cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&cycle_key)
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+
+        if !cycle.is_active {
+            return Err(Error::CycleNotActive);
+        }
+
+        let current_month = Self::get_current_month(&env);
+        if cycle.last_adjustment_month == current_month {
+            return Err(Error::MonthlyAdjustmentLimitReached);
+        }
+
+        env.storage().persistent().remove(&bill_key);
+        Self::unindex_due_bill(&env, bill_id, bill.due_date);
+        Self::drop_invoice(&env, bill_id);
+
+        let cycle_bills_key = DataKey::CycleBills(bill.cycle_id);
+        let cycle_bills: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&cycle_bills_key)
+            .unwrap_or(Vec::new(&env));
+
+        let mut new_bills = Vec::new(&env);
+        for id in cycle_bills.iter() {
+            if id != bill_id {
+                new_bills.push_back(id);
+            }
+        }
+        env.storage().persistent().set(&cycle_bills_key, &new_bills);
+        Self::extend_ttl(&env, &cycle_bills_key);
+
+        cycle.last_adjustment_month = current_month;
+        env.storage().persistent().set(&cycle_key, &cycle);
+        Self::extend_ttl(&env, &cycle_key);
+
+        events::BillCancelled { bill_id }.publish(&env);
+
+        Ok(())
+    }
+
+    // Helper Functions
+
+    // Extend TTL for storage entries
+    fn extend_ttl(env: &Env, key: &DataKey) {
+        env.storage()
+            .persistent()
+            .extend_ttl(key, LEDGER_TTL_THRESHOLD, LEDGER_TTL_EXTEND);
+    }
+
+    // Bring one cycle up to the current layout
+    // Layouts are told apart by field count, since decoding into the wrong struct traps
+    // Version 1 storage predates upgrade, so no deployment of it can reach this code
+    fn migrate_cycle(env: &Env, cycle_id: u64, token: &Address) {
+        let cycle_key = DataKey::Cycle(cycle_id);
+        let old_cycle: BillCycleV3 = match Self::stored_field_count(env, &cycle_key) {
+            Some(12) => {
+                let v2_cycle: BillCycleV2 = env.storage().persistent().get(&cycle_key).unwrap();
+                Self::migrate_cycle_v2(v2_cycle, token)
+            }
+            Some(13) => env.storage().persistent().get(&cycle_key).unwrap(),
+            _ => return, // missing or already current
+        };
+
+        // Version 5 -> 6: cycles can renew automatically and link to the cycle they continue
+        let cycle = BillCycle {
+            user: old_cycle.user,
+            token: old_cycle.token,
+            start_date: old_cycle.start_date,
+            end_date: old_cycle.end_date,
+            total_deposited: old_cycle.total_deposited,
+            operating_fee: old_cycle.operating_fee,
+            fee_percentage: old_cycle.fee_percentage,
+            is_active: old_cycle.is_active,
+            last_adjustment_month: old_cycle.last_adjustment_month,
+            total_disbursed: old_cycle.total_disbursed,
+            emergency_bill_count: old_cycle.emergency_bill_count,
+            emergency_allocated: old_cycle.emergency_allocated,
+            keeper_budget: old_cycle.keeper_budget,
+            auto_renew: false,
+            previous_cycle_id: None,
+            next_cycle_id: None,
+        };
+
+        env.storage().persistent().set(&cycle_key, &cycle);
+        Self::extend_ttl(env, &cycle_key);
+    }
+
+    // Bring a cycle's bills up to the current layout
+    fn migrate_cycle_bills(env: &Env, cycle_id: u64) {
+        let bill_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::CycleBills(cycle_id))
+            .unwrap_or(Vec::new(env));
+
+        for bill_id in bill_ids.iter() {
+            let bill_key = DataKey::Bill(bill_id);
+            let old_bill: BillV3 = match Self::stored_field_count(env, &bill_key) {
+                Some(12) => {
+                    let v2_bill: BillV2 = env.storage().persistent().get(&bill_key).unwrap();
+                    Self::migrate_bill_v2(v2_bill)
+                }
+                Some(15) => env.storage().persistent().get(&bill_key).unwrap(),
+                _ => continue,
+            };
+
+            // Version 8 -> 9: bills can have a variable amount; older bills are fixed
+            let bill = Bill {
+                id: old_bill.id,
+                cycle_id: old_bill.cycle_id,
+                name: old_bill.name,
+                amount: old_bill.amount,
+                due_date: old_bill.due_date,
+                is_paid: old_bill.is_paid,
+                is_recurring: old_bill.is_recurring,
+                recurrence_calendar: old_bill.recurrence_calendar,
+                last_paid_date: old_bill.last_paid_date,
+                category: old_bill.category,
+                payee: old_bill.payee,
+                is_emergency: old_bill.is_emergency,
+                installment_count: old_bill.installment_count,
+                installments_paid: old_bill.installments_paid,
+                remaining_balance: old_bill.remaining_balance,
+                is_variable: false,
+                actual_amount: None,
+            };
+
+            env.storage().persistent().set(&bill_key, &bill);
+            Self::extend_ttl(env, &bill_key);
+        }
+    }
+
+    // Version 7 -> 8: bills gained installment plans; older bills have none
+    fn migrate_bill_v2(old_bill: BillV2) -> BillV3 {
+        BillV3 {
+            id: old_bill.id,
+            cycle_id: old_bill.cycle_id,
+            name: old_bill.name,
+            amount: old_bill.amount,
+            due_date: old_bill.due_date,
+            is_paid: old_bill.is_paid,
+            is_recurring: old_bill.is_recurring,
+            recurrence_calendar: old_bill.recurrence_calendar,
+            last_paid_date: old_bill.last_paid_date,
+            category: old_bill.category,
+            payee: old_bill.payee,
+            is_emergency: old_bill.is_emergency,
+            installment_count: 0,
+            installments_paid: 0,
+            remaining_balance: 0,
+        }
+    }
+
+    // Version 2 -> 3: cycles record the token they were funded in, which until now
+    // was always the global token
+    fn migrate_cycle_v2(old_cycle: BillCycleV2, token: &Address) -> BillCycleV3 {
+        BillCycleV3 {
+            user: old_cycle.user,
+            token: token.clone(),
+            start_date: old_cycle.start_date,
+            end_date: old_cycle.end_date,
+            total_deposited: old_cycle.total_deposited,
+            operating_fee: old_cycle.operating_fee,
+            fee_percentage: old_cycle.fee_percentage,
+            is_active: old_cycle.is_active,
+            last_adjustment_month: old_cycle.last_adjustment_month,
+            total_disbursed: old_cycle.total_disbursed,
+            emergency_bill_count: old_cycle.emergency_bill_count,
+            emergency_allocated: old_cycle.emergency_allocated,
+            keeper_budget: old_cycle.keeper_budget,
+        }
+    }
+
+    fn stored_field_count(env: &Env, key: &DataKey) -> Option<u32> {
+        let val: Val = env.storage().persistent().get(key)?;
+        Map::<Symbol, Val>::try_from_val(env, &val)
+            .ok()
+            .map(|fields| fields.len())
+    }
+
+    // Add a bill to the due-date index bucket for its UTC day
+    fn index_due_bill(env: &Env, bill_id: u64, due_date: u64) {
+        let due_bills_key = DataKey::DueBills(due_date / 86400);
+        let mut due_bills: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&due_bills_key)
+            .unwrap_or(Vec::new(env));
+        due_bills.push_back(bill_id);
+        env.storage().persistent().set(&due_bills_key, &due_bills);
+        Self::extend_ttl(env, &due_bills_key);
+    }
+
+    // Remove a bill from the due-date index bucket for its UTC day
+    fn unindex_due_bill(env: &Env, bill_id: u64, due_date: u64) {
+        let due_bills_key = DataKey::DueBills(due_date / 86400);
+        let mut due_bills: Vec<u64> = match env.storage().persistent().get(&due_bills_key) {
+            Some(due_bills) => due_bills,
+            None => return,
+        };
+
+        if let Some(index) = due_bills.first_index_of(bill_id) {
+            due_bills.remove(index);
+        }
+
+        if due_bills.is_empty() {
+            env.storage().persistent().remove(&due_bills_key);
+        } else {
+            env.storage().persistent().set(&due_bills_key, &due_bills);
+            Self::extend_ttl(env, &due_bills_key);
+        }
+    }
+
+    // Calculate total amount allocated to existing bills in a cycle
+    // Already disbursed funds plus every occurrence still to be paid
+    fn calculate_total_allocation(
+        env: &Env,
+        cycle_id: u64,
+        cycle: &BillCycle,
+    ) -> Result<i128, Error> {
+        let cycle_bills_key = DataKey::CycleBills(cycle_id);
+        let bill_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&cycle_bills_key)
+            .unwrap_or(Vec::new(env));
+
+        let mut total: i128 = cycle.total_disbursed;
+
+        for bill_id in bill_ids.iter() {
+            let bill_key = DataKey::Bill(bill_id);
+            if let Some(bill) = env.storage().persistent().get::<DataKey, Bill>(&bill_key) {
+                Self::extend_ttl(env, &bill_key);
+                if bill.is_paid {
+                    continue;
+                }
+                total += Self::bill_reservation(&bill, bill.due_date, cycle.end_date)?;
+            }
+        }
+
+        Ok(total)
+    }
+
+    // An invoice covers one occurrence, so it goes when that occurrence is skipped, moved
+    // or removed without being paid
+    fn drop_invoice(env: &Env, bill_id: u64) {
+        let invoice_key = DataKey::Invoice(bill_id);
+        if env.storage().persistent().has(&invoice_key) {
+            env.storage().persistent().remove(&invoice_key);
+            events::InvoiceDropped { bill_id }.publish(env);
+        }
+    }
+
+    // Load an invoice for the owner of its bill's cycle
+    fn owner_invoice(env: &Env, bill_id: u64) -> Result<(Invoice, DataKey), Error> {
+        let bill: Bill = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Bill(bill_id))
+            .ok_or(Error::BillNotFound)?;
+        let cycle: BillCycle = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Cycle(bill.cycle_id))
+            .ok_or(Error::CycleNotFound)?;
+
+        cycle.user.require_auth();
+
+        let invoice_key = DataKey::Invoice(bill_id);
+        let invoice: Invoice = env
+            .storage()
+            .persistent()
+            .get(&invoice_key)
+            .ok_or(Error::InvoiceNotFound)?;
+        Ok((invoice, invoice_key))
+    }
+
+    // What the vault owes on a cycle: undisbursed deposits plus the unspent keeper budget
+    fn cycle_liability(cycle: &BillCycle) -> i128 {
+        if !cycle.is_active {
+            return 0;
+        }
+        cycle.total_deposited - cycle.operating_fee - cycle.total_disbursed + cycle.keeper_budget
+    }
+
+    // Keep the per-token liability totals in step with every cycle write that moves money
+    fn adjust_liabilities(env: &Env, token: &Address, delta: i128) {
+        let key = DataKey::Liabilities(token.clone());
+        let liabilities: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &(liabilities + delta));
+
+        let mut tokens = Self::liability_tokens(env);
+        if !tokens.contains(token) {
+            tokens.push_back(token.clone());
+            env.storage()
+                .instance()
+                .set(&DataKey::LiabilityTokens, &tokens);
+        }
+    }
+
+    fn liability_tokens(env: &Env) -> Vec<Address> {
+        env.storage()
+            .instance()
+            .get(&DataKey::LiabilityTokens)
+            .unwrap_or(Vec::new(env))
+    }
+
+    // Deposits left for bills once the operating fee is taken
+    fn available_funds(cycle: &BillCycle) -> i128 {
+        cycle.total_deposited - cycle.operating_fee
+    }
+
+    // Amount a bill reserves in its cycle for occurrences from due_date on
+    // Installment bills reserve no more than the balance they have left
+    fn bill_reservation(bill: &Bill, due_date: u64, end_date: u64) -> Result<i128, Error> {
+        let occurrences = Self::count_occurrences(
+            due_date,
+            bill.is_recurring,
+            &bill.recurrence_calendar,
+            end_date,
+        )?;
+        if bill.installment_count == 0 {
+            return Ok(bill.amount * occurrences);
+        }
+
+        // The balance already caps the installments left, the last of which may be smaller
+        Ok((bill.amount * occurrences).min(bill.remaining_balance))
+    }
+
+    // Amount the next payment of a bill transfers; the last installment may be smaller
+    // Variable bills pay the amount set for the current occurrence
+    fn payment_amount(bill: &Bill) -> Result<i128, Error> {
+        if bill.is_variable {
+            return bill.actual_amount.ok_or(Error::BillAmountNotSet);
+        }
+        if bill.installment_count == 0 {
+            return Ok(bill.amount);
+        }
+        Ok(bill.amount.min(bill.remaining_balance))
+    }
+
+    // Validate that adding a new bill won't exceed available funds
+    fn validate_allocation(
+        env: &Env,
+        cycle_id: u64,
+        cycle: &BillCycle,
+        new_bill_cost: i128,
+    ) -> Result<(), Error> {
+        let existing_allocation = Self::calculate_total_allocation(env, cycle_id, cycle)?;
+
+        let total_allocation = existing_allocation + new_bill_cost;
+
+        if total_allocation > Self::available_funds(cycle) {
+            return Err(Error::InsufficientFunds);
+        }
+
+        Ok(())
+    }
+
+    // Number of payments a bill still has to make, starting from its current due date
+    fn count_occurrences(
+        due_date: u64,
+        is_recurring: bool,
+        recurrence_calendar: &Vec<u32>,
+        end_date: u64,
+    ) -> Result<i128, Error> {
+        if !is_recurring {
+            return Ok(1);
+        }
+
+        let mut occurrences: i128 = 1;
+        let mut current = due_date;
+        while let Some(next) =
+            Self::next_scheduled_due_date(current, recurrence_calendar, end_date)?
+        {
+            occurrences += 1;
+            current = next;
+        }
+
+        Ok(occurrences)
+    }
+
+    // Use up the approvals recorded for this exact action
+    // Only approvals from current signers count towards the threshold
+    fn require_admin(env: &Env, action: AdminAction) -> Result<(), Error> {
+        let signers = Self::admin_signers(env.clone());
+        if signers.is_empty() {
+            return Err(Error::AdminNotSet);
+        }
+
+        let approvals = Self::get_admin_approvals(env.clone(), action.clone());
+        let mut count = 0u32;
+        for approver in approvals.iter() {
+            if signers.contains(&approver) {
+                count += 1;
+            }
+        }
+        if count < Self::admin_threshold(env.clone()) {
+            return Err(Error::InsufficientApprovals);
+        }
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::AdminApprovals(action));
+        Ok(())
+    }
+
+    fn require_not_paused(env: &Env, scope: PauseScope) -> Result<(), Error> {
+        Self::require_migrated(env)?;
+        if Self::is_paused(env, scope) {
+            return Err(Error::ContractPaused);
+        }
+        Ok(())
+    }
+
+    // Writes wait until migrate has finished, since it rebuilds liabilities from the cycles
+    // as it reaches them and an earlier change to a cycle would be counted twice
+    fn require_migrated(env: &Env) -> Result<(), Error> {
+        if Self::version(env) < CONTRACT_VERSION {
+            return Err(Error::MigrationInProgress);
+        }
+        Ok(())
+    }
+
+    // A cycle migrate has reached is in the current layout and counted in the liabilities
+    fn require_cycle_migrated(env: &Env, cycle_id: u64) -> Result<(), Error> {
+        if Self::version(env) >= CONTRACT_VERSION {
+            return Ok(());
+        }
+
+        let all_cycles: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::AllCycles)
+            .unwrap_or(Vec::new(env));
+        let migrated: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::MigrationCursor)
+            .unwrap_or(0);
+        match all_cycles.first_index_of(cycle_id) {
+            Some(index) if index < migrated => Ok(()),
+            _ => Err(Error::MigrationInProgress),
+        }
+    }
+
+    // Role holders act alone; a signer without the role needs the action approved
+    fn require_role(
+        env: &Env,
+        caller: &Address,
+        role: Role,
+        action: AdminAction,
+    ) -> Result<(), Error> {
+        caller.require_auth();
+        if Self::has_role(env.clone(), role, caller.clone()) {
+            return Ok(());
+        }
+        Self::require_approved_signer(env, caller, action)
+    }
+
+    fn require_signer(env: &Env, caller: &Address, action: AdminAction) -> Result<(), Error> {
+        caller.require_auth();
+        Self::require_approved_signer(env, caller, action)
+    }
+
+    // Caller must already be authenticated
+    fn require_approved_signer(
+        env: &Env,
+        caller: &Address,
+        action: AdminAction,
+    ) -> Result<(), Error> {
+        if !Self::admin_signers(env.clone()).contains(caller) {
+            return Err(Error::Unauthorized);
+        }
+        Self::require_admin(env, action)
+    }
+
+    // Fee changes belong to the fee manager, the default token to the admin signers
+    fn require_config_authority(
+        env: &Env,
+        caller: &Address,
+        change: &ConfigChange,
+        action: AdminAction,
+    ) -> Result<(), Error> {
+        match change {
+            ConfigChange::UsdcToken(_) => Self::require_signer(env, caller, action),
+            ConfigChange::FeePercentage(_, _)
+            | ConfigChange::FeeRecipient(_)
+            | ConfigChange::EarlyWithdrawalPenalty(_) => {
+                Self::require_role(env, caller, Role::FeeManager, action)
+            }
+        }
+    }
+
+    fn validate_config_change(env: &Env, change: &ConfigChange) -> Result<(), Error> {
+        match change {
+            ConfigChange::FeePercentage(token, fee_percentage) => {
+                Self::validate_fee_percentage(*fee_percentage)?;
+                if !Self::allowed_tokens(env).contains(token) {
+                    return Err(Error::TokenNotAllowed);
+                }
+                Ok(())
+            }
+            ConfigChange::FeeRecipient(_) => Ok(()),
+            ConfigChange::UsdcToken(token) => {
+                if !Self::allowed_tokens(env).contains(token) {
+                    return Err(Error::TokenNotAllowed);
+                }
+                Ok(())
+            }
+            ConfigChange::EarlyWithdrawalPenalty(penalty_bps) => {
+                if *penalty_bps > MAX_EARLY_WITHDRAWAL_PENALTY_BPS {
+                    return Err(Error::InvalidPenalty);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn apply_config_change(env: &Env, change: ConfigChange) {
+        match change {
+            ConfigChange::FeePercentage(token, fee_percentage) => {
+                env.storage()
+                    .instance()
+                    .set(&DataKey::TokenFeePercentage(token.clone()), &fee_percentage);
+
+                events::FeePercentageUpdated {
+                    token,
+                    fee_percentage,
+                }
+                .publish(env);
+            }
+            ConfigChange::FeeRecipient(recipient) => {
+                env.storage()
+                    .instance()
+                    .set(&DataKey::FeeRecipient, &recipient);
+
+                events::FeeRecipientUpdated { recipient }.publish(env);
+            }
+            ConfigChange::UsdcToken(token) => {
+                env.storage().instance().set(&DataKey::UsdcToken, &token);
+
+                events::UsdcTokenUpdated { token }.publish(env);
+            }
+            ConfigChange::EarlyWithdrawalPenalty(penalty_bps) => {
+                env.storage()
+                    .instance()
+                    .set(&DataKey::EarlyWithdrawalPenalty, &penalty_bps);
+
+                events::EarlyWithdrawalPenaltyUpdated { penalty_bps }.publish(env);
+            }
+        }
+    }
+
+    fn remove_proposal(env: &Env, proposal_id: u64) {
+        env.storage()
+            .instance()
+            .remove(&DataKey::Proposal(proposal_id));
+
+        let mut pending = Self::get_pending_proposals(env.clone());
+        if let Some(index) = pending.first_index_of(proposal_id) {
+            pending.remove(index);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::PendingProposals, &pending);
+    }
+
+    fn next_proposal_id(env: &Env) -> u64 {
+        let counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ProposalCounter)
+            .unwrap_or(0);
+        let next_id = counter + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::ProposalCounter, &next_id);
+        next_id
+    }
+
+    // Get and increment cycle counter
+    // IDs start from 1 instead of 0
+    fn next_cycle_id(env: &Env) -> u64 {
+        let counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::CycleCounter)
+            .unwrap_or(0);
+        let next_id = counter + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::CycleCounter, &next_id);
+        next_id
+    }
+
+    // Get and increment bill counter
+    // IDs start from 1 instead of 0
+    fn next_bill_id(env: &Env) -> u64 {
+        let counter: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::BillCounter)
+            .unwrap_or(0);
+        let next_id = counter + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::BillCounter, &next_id);
+        next_id
+    }
+
+    fn validate_fee_percentage(fee_percentage: u32) -> Result<(), Error> {
+        if fee_percentage < 100 || fee_percentage > 500 {
+            return Err(Error::InvalidFeePercentage);
+        }
+        Ok(())
+    }
+
+    // Calculate operating fee
+    fn calculate_fee(amount: i128, fee_percentage: u32) -> i128 {
+        (amount * fee_percentage as i128) / 10000
+    }
+
+    // Portion of an operating fee kept in the vault as keeper budget
+    fn calculate_keeper_budget(env: &Env, operating_fee: i128) -> i128 {
+        (operating_fee * Self::keeper_tip(env).fee_share_bps as i128) / 10000
+    }
+
+    /// Internal helper for ending cycles - shared logic for end_cycle, admin_end_cycle and renewals
+    /// Returns the surplus; without refund_surplus it stays in the vault for the caller to reuse
+    fn end_cycle_internal(
+        env: &Env,
+        cycle_id: u64,
+        mut cycle: BillCycle,
+        cycle_key: DataKey,
+        refund_surplus: bool,
+    ) -> Result<i128, Error> {
+        if !cycle.is_active {
+            return Err(Error::CycleAlreadyEnded);
+        }
+
+        // Outstanding bills of an ended cycle are no longer due
+        let cycle_bills_key = DataKey::CycleBills(cycle_id);
+        let bill_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&cycle_bills_key)
+            .unwrap_or(Vec::new(env));
+
+        for bill_id in bill_ids.iter() {
+            let bill_key = DataKey::Bill(bill_id);
+            if let Some(bill) = env.storage().persistent().get::<DataKey, Bill>(&bill_key) {
+                if !bill.is_paid {
+                    Self::unindex_due_bill(env, bill_id, bill.due_date);
+                    Self::drop_invoice(env, bill_id);
+                }
+            }
+        }
+
+        // Everything not already disbursed to payees is returned to the user
+        let surplus = cycle.total_deposited - cycle.operating_fee - cycle.total_disbursed;
+
+        // Unspent keeper budget is part of the operating fee
+        let unused_keeper_budget = cycle.keeper_budget;
+
+        // A rolled over surplus is counted again by the cycle that receives it
+        Self::adjust_liabilities(env, &cycle.token, -Self::cycle_liability(&cycle));
+
+        cycle.is_active = false;
+        cycle.keeper_budget = 0;
+        env.storage().persistent().set(&cycle_key, &cycle);
+        Self::extend_ttl(env, &cycle_key);
+
+        let token_client = token::TokenClient::new(env, &cycle.token);
+
+        if surplus > 0 && refund_surplus {
+            token_client.transfer(&env.current_contract_address(), &cycle.user, &surplus);
+        }
+
+        if unused_keeper_budget > 0 {
+            let fee_recipient = Self::fee_recipient(env)?;
+            token_client.transfer(
+                &env.current_contract_address(),
+                &fee_recipient,
+                &unused_keeper_budget,
+            );
+        }
+
+        events::CycleEnded { cycle_id, surplus }.publish(env);
+
+        Ok(surplus)
+    }
+
+    // Store a new cycle, pull user_deposit of its amount from the user and send the fee out
+    // Callers check auth and pause state
+    #[allow(clippy::too_many_arguments)]
+    fn open_cycle(
+        env: &Env,
+        user: Address,
+        token: Address,
+        duration_months: u32,
+        amount: i128,
+        user_deposit: i128,
+        previous_cycle_id: Option<u64>,
+        auto_renew: bool,
+    ) -> Result<u64, Error> {
+        if !(1..=12).contains(&duration_months) {
+            return Err(Error::InvalidCycleDuration);
+        }
+        if amount <= 0 {
+            return Err(Error::InsufficientFunds);
+        }
+
+        let fee_percentage = Self::fee_percentage(env, token.clone())?;
+
+        let operating_fee = Self::calculate_fee(amount, fee_percentage);
+        let keeper_budget = Self::calculate_keeper_budget(env, operating_fee);
+        let current_time = env.ledger().timestamp();
+        let end_date = Self::add_months(current_time, duration_months)?;
+
+        let cycle_id = Self::next_cycle_id(env);
+
+        // Clone user for keys and events
+        let user_for_keys = user.clone();
+        let user_for_event = user.clone();
+        let cycle = BillCycle {
+            user, // Move original user into struct
+            token,
+            start_date: current_time,
+            end_date,
+            total_deposited: amount,
+            operating_fee,
+            fee_percentage,
+            is_active: true,
+            total_disbursed: 0,
+            emergency_bill_count: 0,
+            emergency_allocated: 0,
+            keeper_budget,
+            // last_adjustment_month: Self::get_current_month(&env),
+            last_adjustment_month: 0,
+            auto_renew,
+            previous_cycle_id,
+            next_cycle_id: None,
+        };
+
+        let cycle_key = DataKey::Cycle(cycle_id);
+        env.storage().persistent().set(&cycle_key, &cycle);
+        Self::extend_ttl(env, &cycle_key);
+        Self::adjust_liabilities(env, &cycle.token, Self::cycle_liability(&cycle));
+
+        let user_cycles_key = DataKey::UserCycles(user_for_keys.clone());
+        let mut user_cycles: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&user_cycles_key)
+            .unwrap_or(Vec::new(env));
+        user_cycles.push_back(cycle_id);
+        env.storage()
+            .persistent()
+            .set(&user_cycles_key, &user_cycles);
+        Self::extend_ttl(env, &user_cycles_key);
+
+        let all_cycles_key = DataKey::AllCycles;
+        let mut all_cycles: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&all_cycles_key)
+            .unwrap_or(Vec::new(env));
+        all_cycles.push_back(cycle_id);
+        env.storage().persistent().set(&all_cycles_key, &all_cycles);
+        Self::extend_ttl(env, &all_cycles_key);
+
+        let cycle_bills_key = DataKey::CycleBills(cycle_id);
+        let empty_bills: Vec<u64> = Vec::new(env);
+        env.storage()
+            .persistent()
+            .set(&cycle_bills_key, &empty_bills);
+        Self::extend_ttl(env, &cycle_bills_key);
+
+        let token_client = token::TokenClient::new(env, &cycle.token);
+        if user_deposit > 0 {
+            token_client.transfer(
+                &user_for_keys,
+                env.current_contract_address(),
+                &user_deposit,
+            );
+        }
+
+        // The keeper budget stays in the vault to fund execute_due_bill tips
+        let fee_recipient = Self::fee_recipient(env)?;
+        token_client.transfer(
+            &env.current_contract_address(),
+            &fee_recipient,
+            &(operating_fee - keeper_budget),
+        );
+
+        events::CycleCreated {
+            cycle_id,
+            user: user_for_event,
+        }
+        .publish(env);
+
+        Ok(cycle_id)
+    }
+
+    // Shared by renew_cycle and auto-renewing end_cycle
+    // Every check runs before the first write, so an error leaves storage untouched
+    fn renew_cycle_internal(
+        env: &Env,
+        cycle_id: u64,
+        mut cycle: BillCycle,
+        cycle_key: DataKey,
+        duration_months: u32,
+        extra_deposit: i128,
+    ) -> Result<u64, Error> {
+        if !cycle.is_active {
+            return Err(Error::CycleAlreadyEnded);
+        }
+        if !(1..=12).contains(&duration_months) {
+            return Err(Error::InvalidCycleDuration);
+        }
+
+        let surplus = cycle.total_deposited - cycle.operating_fee - cycle.total_disbursed;
+        let rolled_over = surplus.max(0);
+        let amount = rolled_over + extra_deposit;
+        if amount <= 0 {
+            return Err(Error::InsufficientFunds);
+        }
+
+        // The new cycle pays the operating fee on its whole deposit, like create_cycle
+        let fee_percentage = Self::fee_percentage(env, cycle.token.clone())?;
+        let available = amount - Self::calculate_fee(amount, fee_percentage);
+
+        let start_date = env.ledger().timestamp();
+        let end_date = Self::add_months(start_date, duration_months)?;
+
+        // Recurring bills carry over on their usual schedule; emergency bills don't
+        let bill_ids: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::CycleBills(cycle_id))
+            .unwrap_or(Vec::new(env));
+        let mut renewed_bills: Vec<Bill> = Vec::new(env);
+        let mut allocation: i128 = 0;
+        for bill_id in bill_ids.iter() {
+            let mut bill: Bill = match env.storage().persistent().get(&DataKey::Bill(bill_id)) {
+                Some(bill) => bill,
+                None => continue,
+            };
+            if !bill.is_recurring || bill.is_emergency {
+                continue;
+            }
+            // Installment plans carry over until they are paid off
+            if bill.installment_count > 0 && bill.remaining_balance == 0 {
+                continue;
+            }
+            let due_date = match Self::rebase_due_date(
+                bill.due_date,
+                &bill.recurrence_calendar,
+                start_date,
+                end_date,
+            )? {
+                Some(due_date) => due_date,
+                None => continue,
+            };
+            allocation += Self::bill_reservation(&bill, due_date, end_date)?;
+            bill.due_date = due_date;
+            renewed_bills.push_back(bill);
+        }
+        if allocation > available {
+            return Err(Error::InsufficientFunds);
+        }
+
+        let new_cycle_id = Self::open_cycle(
+            env,
+            cycle.user.clone(),
+            cycle.token.clone(),
+            duration_months,
+            amount,
+            extra_deposit,
+            Some(cycle_id),
+            cycle.auto_renew,
+        )?;
+
+        let mut new_bill_ids: Vec<u64> = Vec::new(env);
+        for old_bill in renewed_bills.iter() {
+            let bill_id = Self::next_bill_id(env);
+            let bill = Bill {
+                id: bill_id,
+                cycle_id: new_cycle_id,
+                name: old_bill.name,
+                amount: old_bill.amount,
+                due_date: old_bill.due_date,
+                is_paid: false,
+                is_recurring: true,
+                recurrence_calendar: old_bill.recurrence_calendar,
+                last_paid_date: None,
+                category: old_bill.category,
+                payee: old_bill.payee,
+                is_emergency: false,
+                installment_count: old_bill.installment_count,
+                installments_paid: old_bill.installments_paid,
+                remaining_balance: old_bill.remaining_balance,
+                is_variable: old_bill.is_variable,
+                actual_amount: None,
+            };
 
-            let cycle_bills_key = DataKey::CycleBills(bill.cycle_id);
-            let cycle_bills: Vec<u64> = env
-                .storage()
-                .persistent()
-                .get(&cycle_bills_key)
-                .unwrap_or(Vec::new(&env));
+            let bill_key = DataKey::Bill(bill_id);
+            env.storage().persistent().set(&bill_key, &bill);
+            Self::extend_ttl(env, &bill_key);
+            Self::index_due_bill(env, bill_id, bill.due_date);
+            new_bill_ids.push_back(bill_id);
 
-            let mut new_bills = Vec::new(&env);
-            for id in cycle_bills.iter() {
-                if id != bill_id {
-                    new_bills.push_back(id);
-                }
+            events::BillAdded {
+                bill_id,
+                cycle_id: new_cycle_id,
             }
-            env.storage().persistent().set(&cycle_bills_key, &new_bills);
-            Self::extend_ttl(&env, &cycle_bills_key);
+            .publish(env);
         }
 
-        cycle.last_adjustment_month = current_month;
-        env.storage().persistent().set(&cycle_key, &cycle);
-        Self::extend_ttl(&env, &cycle_key);
+        let cycle_bills_key = DataKey::CycleBills(new_cycle_id);
+        env.storage()
+            .persistent()
+            .set(&cycle_bills_key, &new_bill_ids);
+        Self::extend_ttl(env, &cycle_bills_key);
 
-        events::BillCancelled { bill_id }.publish(&env);
+        cycle.next_cycle_id = Some(new_cycle_id);
+        Self::end_cycle_internal(env, cycle_id, cycle, cycle_key, false)?;
 
-        Ok(())
+        events::CycleRenewed {
+            cycle_id,
+            new_cycle_id,
+            rolled_over,
+        }
+        .publish(env);
+
+        Ok(new_cycle_id)
     }
 
-    /// Delete a bill completely (all future occurrences)
-    pub fn delete_bill(env: Env, bill_id: u64) -> Result<(), Error> {
+    // First scheduled occurrence of a recurring bill inside [start_date, end_date)
+    // Keeps the bill's day of month
+    fn rebase_due_date(
+        due_date: u64,
+        recurrence_calendar: &Vec<u32>,
+        start_date: u64,
+        end_date: u64,
+    ) -> Result<Option<u64>, Error> {
+        let mut months = 0;
+        loop {
+            let candidate = Self::add_months(due_date, months)?;
+            if candidate >= end_date {
+                return Ok(None);
+            }
+            if candidate >= start_date && Self::is_scheduled_month(recurrence_calendar, candidate)?
+            {
+                return Ok(Some(candidate));
+            }
+            months += 1;
+        }
+    }
+
+    // Checks and pays a single bill for the keeper paths, without auth or the reentrancy guard
+    // All checks run before any state is written so a failure leaves storage untouched
+    fn process_bill_payment(env: &Env, bill_id: u64) -> Result<(), Error> {
         let bill_key = DataKey::Bill(bill_id);
         let bill: Bill = env
             .storage()
@@ -728,261 +3022,318 @@ impl LockedIn {
             .get(&bill_key)
             .ok_or(Error::BillNotFound)?;
 
+        if bill.is_paid {
+            return Err(Error::BillAlreadyPaid);
+        }
+
         let cycle_key = DataKey::Cycle(bill.cycle_id);
-        let mut cycle: BillCycle = env
+        let cycle: BillCycle = env
             .storage()
             .persistent()
             .get(&cycle_key)
             .ok_or(Error::CycleNotFound)?;
 
-        cycle.user.require_auth();
-
         if !cycle.is_active {
             return Err(Error::CycleNotActive);
         }
 
-        let current_month = Self::get_current_month(&env);
-        if cycle.last_adjustment_month == current_month {
-            return Err(Error::MonthlyAdjustmentLimitReached);
+        Self::pay_bill_internal(env, bill_id, bill, bill_key, cycle, cycle_key)
+    }
+
+    fn process_bill_payments(env: &Env, bill_ids: Vec<u64>) -> Vec<PaymentResult> {
+        let mut results = Vec::new(env);
+        for bill_id in bill_ids.iter() {
+            let error = Self::process_bill_payment(env, bill_id)
+                .err()
+                .map(|e| e as u32);
+            results.push_back(PaymentResult {
+                bill_id,
+                paid: error.is_none(),
+                error,
+            });
         }
+        results
+    }
 
-        env.storage().persistent().remove(&bill_key);
+    /// Internal helper for paying bills - shared logic for pay_bill and admin_pay_bill
+    /// Records the disbursement on the cycle so end_cycle never refunds money already paid out
+    fn pay_bill_internal(
+        env: &Env,
+        bill_id: u64,
+        mut bill: Bill,
+        bill_key: DataKey,
+        mut cycle: BillCycle,
+        cycle_key: DataKey,
+    ) -> Result<(), Error> {
+        let current_time = env.ledger().timestamp();
+        let window = Self::payment_window(env);
+        let bill_due_day_start = (bill.due_date / 86400) * 86400;
+        let current_day_start = (current_time / 86400) * 86400;
 
-        let cycle_bills_key = DataKey::CycleBills(bill.cycle_id);
-        let cycle_bills: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&cycle_bills_key)
-            .unwrap_or(Vec::new(&env));
+        // Recurring bills only move to the next scheduled month once paid,
+        // so an early attempt is either a repeat payment or a skipped month
+        if current_day_start + window.days_before as u64 * 86400 < bill_due_day_start {
+            if let Some(last_paid) = bill.last_paid_date {
+                if Self::get_month(last_paid) == Self::get_month(current_time) {
+                    return Err(Error::BillAlreadyPaid);
+                }
+            }
+            return Err(Error::BillNotDueYet);
+        }
 
-        let mut new_bills = Vec::new(&env);
-        for id in cycle_bills.iter() {
-            if id != bill_id {
-                new_bills.push_back(id);
+        let is_late = current_day_start > bill_due_day_start + window.days_after as u64 * 86400;
+
+        // Resolve everything that can fail before writing, batch payments rely on it
+        // An approved invoice settles its amount; a pending one holds the payment until the
+        // owner approves or rejects it
+        let invoice_key = DataKey::Invoice(bill_id);
+        let invoice: Option<Invoice> = env.storage().persistent().get(&invoice_key);
+        let amount = match invoice {
+            Some(invoice) if invoice.is_approved => invoice.amount,
+            Some(_) => return Err(Error::InvoicePendingApproval),
+            None => Self::payment_amount(&bill)?,
+        };
+        if bill.installment_count > 0 {
+            bill.installments_paid += 1;
+            bill.remaining_balance -= amount;
+        }
+        let is_paid_off = bill.installment_count > 0 && bill.remaining_balance == 0;
+        let next_due_date = if bill.is_recurring && !is_paid_off {
+            Self::next_scheduled_due_date(bill.due_date, &bill.recurrence_calendar, cycle.end_date)?
+        } else {
+            None
+        };
+
+        // Transfer before writing so a failed transfer leaves the bill untouched
+        let token_client = token::TokenClient::new(env, &cycle.token);
+        if !matches!(
+            token_client.try_transfer(&env.current_contract_address(), &bill.payee, &amount),
+            Ok(Ok(()))
+        ) {
+            return Err(Error::TokenTransferFailed);
+        }
+
+        bill.last_paid_date = Some(current_time);
+        bill.actual_amount = None;
+        env.storage().persistent().remove(&invoice_key);
+        Self::unindex_due_bill(env, bill_id, bill.due_date);
+
+        match next_due_date {
+            Some(next_due_date) => {
+                bill.due_date = next_due_date;
+                bill.is_paid = false;
+                Self::index_due_bill(env, bill_id, next_due_date);
             }
+            None => bill.is_paid = true,
         }
-        env.storage().persistent().set(&cycle_bills_key, &new_bills);
-        Self::extend_ttl(&env, &cycle_bills_key);
 
-        cycle.last_adjustment_month = current_month;
+        env.storage().persistent().set(&bill_key, &bill);
+        Self::extend_ttl(env, &bill_key);
+
+        cycle.total_disbursed += amount;
         env.storage().persistent().set(&cycle_key, &cycle);
-        Self::extend_ttl(&env, &cycle_key);
+        Self::extend_ttl(env, &cycle_key);
+        Self::adjust_liabilities(env, &cycle.token, -amount);
 
-        events::BillCancelled { bill_id }.publish(&env);
+        events::BillPaid {
+            bill_id,
+            amount,
+            payee: bill.payee,
+            is_late,
+        }
+        .publish(env);
 
         Ok(())
     }
 
-    // Helper Functions
-
-    // Extend TTL for storage entries
-    fn extend_ttl(env: &Env, key: &DataKey) {
-        env.storage()
-            .persistent()
-            .extend_ttl(key, LEDGER_TTL_THRESHOLD, LEDGER_TTL_EXTEND);
+    // Whole calendar months from one timestamp's month to another's
+    fn months_between(start: u64, end: u64) -> u32 {
+        let start_month = Self::get_month(start);
+        let end_month = Self::get_month(end);
+        (end_month / 100 * 12 + end_month % 100) - (start_month / 100 * 12 + start_month % 100)
     }
 
-    // Calculate total amount allocated to existing bills in a cycle
-    fn calculate_total_allocation(env: &Env, cycle_id: u64, cycle: &BillCycle) -> Result<i128, Error> {
-        let cycle_bills_key = DataKey::CycleBills(cycle_id);
-        let bill_ids: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&cycle_bills_key)
-            .unwrap_or(Vec::new(&env));
+    // Get current month in YYYYMM format using time crate
+    fn get_current_month(env: &Env) -> u32 {
+        Self::get_month(env.ledger().timestamp())
+    }
 
-        let mut total: i128 = 0;
+    // Get the month of a timestamp in YYYYMM format
+    fn get_month(timestamp: u64) -> u32 {
+        let datetime = OffsetDateTime::from_unix_timestamp(timestamp as i64).unwrap();
 
-        for bill_id in bill_ids.iter() {
-            let bill_key = DataKey::Bill(bill_id);
-            if let Some(bill) = env.storage().persistent().get::<DataKey, Bill>(&bill_key) {
-                Self::extend_ttl(env, &bill_key);
-                if bill.is_recurring {
-                    let cycle_duration_days = (cycle.end_date - cycle.start_date) / 86400;
-                    let occurrences = (cycle_duration_days / 30).max(1) as i128;
-                    total += bill.amount * occurrences;
-                } else {
-                    total += bill.amount;
-                }
-            }
-        }
+        let year = datetime.year() as u32;
+        let month = datetime.month() as u32;
 
-        Ok(total)
+        year * 100 + month
     }
 
-    // Validate that adding a new bill won't exceed available funds
-    fn validate_allocation(
-        env: &Env,
-        cycle_id: u64,
-        cycle: &BillCycle,
-        new_bill_amount: i128,
-        is_recurring: bool,
-    ) -> Result<(), Error> {
-        let existing_allocation = Self::calculate_total_allocation(env, cycle_id, cycle)?;
+    // Add calendar months to a timestamp, preserving the day of month where possible
+    fn add_months(timestamp: u64, months: u32) -> Result<u64, Error> {
+        use time::Month;
 
-        let new_bill_cost = if is_recurring {
-            let cycle_duration_days = (cycle.end_date - cycle.start_date) / 86400;
-            let occurrences = (cycle_duration_days / 30).max(1) as i128;
-            new_bill_amount * occurrences
-        } else {
-            new_bill_amount
-        };
+        let datetime = OffsetDateTime::from_unix_timestamp(timestamp as i64)
+            .ok()
+            .ok_or(Error::InvalidTimestamp)?;
 
-        let total_allocation = existing_allocation + new_bill_cost;
-        let available = cycle.total_deposited - cycle.operating_fee;
+        // Zero-based month index counted from the start of the current year
+        let month_index = datetime.month() as u32 - 1 + months;
+        let next_year = datetime.year() + (month_index / 12) as i32;
+        let next_month = Month::try_from((month_index % 12 + 1) as u8)
+            .ok()
+            .ok_or(Error::InvalidTimestamp)?;
 
-        if total_allocation > available {
-            return Err(Error::InsufficientFunds);
-        }
+        // Get the number of days in the target month
+        let days_in_next_month = next_month.length(next_year);
 
-        Ok(())
+        // Use the same day, or the last day of the target month if current day doesn't exist
+        let next_day = datetime.day().min(days_in_next_month);
+
+        // Move to day 1 first so intermediate dates are always valid
+        let next_datetime = datetime
+            .replace_day(1)
+            .ok()
+            .ok_or(Error::InvalidTimestamp)?
+            .replace_year(next_year)
+            .ok()
+            .ok_or(Error::InvalidTimestamp)?
+            .replace_month(next_month)
+            .ok()
+            .ok_or(Error::InvalidTimestamp)?
+            .replace_day(next_day)
+            .ok()
+            .ok_or(Error::InvalidTimestamp)?;
+
+        Ok(next_datetime.unix_timestamp() as u64)
     }
 
-    // Require admin authentication - replaces the pattern: Self::admin() + require_auth()
-    fn require_admin(env: &Env) -> Result<(), Error> {
-        let admin = Self::admin(env.clone())?;
-        admin.require_auth();
+    // Move a recurring bill past its current occurrence without paying it
+    fn skip_occurrence(env: &Env, bill: &mut Bill, end_date: u64) -> Result<(), Error> {
+        Self::unindex_due_bill(env, bill.id, bill.due_date);
+        Self::drop_invoice(env, bill.id);
+        match Self::next_scheduled_due_date(bill.due_date, &bill.recurrence_calendar, end_date)? {
+            Some(next_due_date) => {
+                bill.due_date = next_due_date;
+                Self::index_due_bill(env, bill.id, next_due_date);
+            }
+            None => bill.is_paid = true,
+        }
         Ok(())
     }
 
-    // Get and increment cycle counter
-    // IDs start from 1 instead of 0
-    fn next_cycle_id(env: &Env) -> u64 {
-        let counter: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::CycleCounter)
-            .unwrap_or(0);
-        let next_id = counter + 1;
-        env.storage()
-            .instance()
-            .set(&DataKey::CycleCounter, &next_id);
-        next_id
-    }
+    // Check whether a timestamp falls in one of the bill's recurrence months
+    // An empty calendar means the bill recurs every month
+    fn is_scheduled_month(recurrence_calendar: &Vec<u32>, timestamp: u64) -> Result<bool, Error> {
+        if recurrence_calendar.is_empty() {
+            return Ok(true);
+        }
 
-    // Get and increment bill counter
-    // IDs start from 1 instead of 0
-    fn next_bill_id(env: &Env) -> u64 {
-        let counter: u64 = env
-            .storage()
-            .instance()
-            .get(&DataKey::BillCounter)
-            .unwrap_or(0);
-        let next_id = counter + 1;
-        env.storage()
-            .instance()
-            .set(&DataKey::BillCounter, &next_id);
-        next_id
+        let datetime = OffsetDateTime::from_unix_timestamp(timestamp as i64)
+            .ok()
+            .ok_or(Error::InvalidTimestamp)?;
+        let month = datetime.month() as u32;
+
+        Ok(recurrence_calendar.contains(month))
     }
 
-    fn validate_fee_percentage(fee_percentage: u32) -> Result<(), Error> {
-        if fee_percentage < 100 || fee_percentage > 500 {
-            return Err(Error::InvalidFeePercentage);
+    // Find the next due date after `due_date` that lands in a scheduled month
+    // Returns None once the cycle has no further occurrences
+    fn next_scheduled_due_date(
+        due_date: u64,
+        recurrence_calendar: &Vec<u32>,
+        end_date: u64,
+    ) -> Result<Option<u64>, Error> {
+        let mut months = 1;
+        let mut next_due_date = Self::add_months(due_date, months)?;
+        while next_due_date < end_date {
+            if Self::is_scheduled_month(recurrence_calendar, next_due_date)? {
+                return Ok(Some(next_due_date));
+            }
+            months += 1;
+            next_due_date = Self::add_months(due_date, months)?;
         }
-        Ok(())
-    }
 
-    // Calculate operating fee
-    fn calculate_fee(amount: i128, fee_percentage: u32) -> i128 {
-        (amount * fee_percentage as i128) / 10000
+        Ok(None)
     }
 
-    /// Internal helper for ending cycles - shared logic for end_cycle and admin_end_cycle
-    fn end_cycle_internal(env: &Env, cycle_id: u64, mut cycle: BillCycle, cycle_key: DataKey) -> Result<(), Error> {
-        if !cycle.is_active {
-            return Err(Error::CycleAlreadyEnded);
+    // Checks shared by every scheduled bill: a positive amount, a payee other than the vault,
+    // and a due date inside the cycle on day 1-28 at least the lead time away
+    fn validate_new_bill(
+        env: &Env,
+        cycle: &BillCycle,
+        amount: i128,
+        due_date: u64,
+        payee: &Address,
+    ) -> Result<(), Error> {
+        if amount <= 0 {
+            return Err(Error::InvalidBillAmount);
         }
 
-        let cycle_bills_key = DataKey::CycleBills(cycle_id);
-        let bill_ids: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&cycle_bills_key)
-            .unwrap_or(Vec::new(env));
+        Self::validate_payee(env, payee)?;
 
-        let mut total_paid_bills: i128 = 0;
-        for bill_id in bill_ids.iter() {
-            let bill_key = DataKey::Bill(bill_id);
-            if let Some(types::Bill {
-                amount, is_paid, ..
-            }) = env.storage().persistent().get(&bill_key)
-            {
-                Self::extend_ttl(env, &bill_key);
-                if is_paid {
-                    total_paid_bills += amount;
-                }
-            }
+        if due_date < cycle.start_date || due_date > cycle.end_date {
+            return Err(Error::InvalidDueDate);
         }
 
-        let surplus = cycle.total_deposited - cycle.operating_fee - total_paid_bills;
+        Self::validate_day_of_month(due_date)?;
 
-        cycle.is_active = false;
-        env.storage().persistent().set(&cycle_key, &cycle);
-        Self::extend_ttl(env, &cycle_key);
+        Self::validate_lead_time(env, due_date)
+    }
 
-        if surplus > 0 {
-            let usdc_token = Self::usdc_token(env)?;
-            let token_client = token::TokenClient::new(env, &usdc_token);
-            token_client.transfer(&env.current_contract_address(), &cycle.user, &surplus);
+    // Paying the vault itself would just lock the funds again
+    fn validate_payee(env: &Env, payee: &Address) -> Result<(), Error> {
+        if *payee == env.current_contract_address() {
+            return Err(Error::InvalidAddress);
         }
-
-        events::CycleEnded { cycle_id, surplus }.publish(env);
-
         Ok(())
     }
 
-    // Get current month in YYYYMM format using time crate
-    fn get_current_month(env: &Env) -> u32 {
-        let timestamp = env.ledger().timestamp() as i64;
-        let datetime = OffsetDateTime::from_unix_timestamp(timestamp).unwrap();
-
-        let year = datetime.year() as u32;
-        let month = datetime.month() as u32;
+    // Check a recurrence calendar and move the first occurrence into a scheduled month
+    fn first_scheduled_due_date(
+        due_date: u64,
+        recurrence_calendar: &Vec<u32>,
+        end_date: u64,
+    ) -> Result<u64, Error> {
+        for month in recurrence_calendar.iter() {
+            if !(1..=12).contains(&month) {
+                return Err(Error::InvalidRecurrence);
+            }
+        }
 
-        year * 100 + month
+        if Self::is_scheduled_month(recurrence_calendar, due_date)? {
+            return Ok(due_date);
+        }
+        Self::next_scheduled_due_date(due_date, recurrence_calendar, end_date)?
+            .ok_or(Error::InvalidRecurrence)
     }
 
-    // Add one month to a timestamp, preserving the day of month where possible
-    fn add_one_month(timestamp: u64) -> Result<u64, Error> {
-        use time::Month;
-
-        let datetime = OffsetDateTime::from_unix_timestamp(timestamp as i64)
-            .ok().ok_or(Error::InvalidTimestamp)?;
-
-        let current_month = datetime.month();
-        let current_year = datetime.year();
-        let current_day = datetime.day();
-
-        // Calculate next month
-        let (next_month, next_year) = if current_month == Month::December {
-            (Month::January, current_year + 1)
-        } else {
-            (current_month.next(), current_year)
-        };
-
-        // Get the number of days in the next month
-        let days_in_next_month = next_month.length(next_year);
-
-        // Use the same day, or the last day of next month if current day doesn't exist
-        let next_day = if current_day > days_in_next_month {
-            days_in_next_month
-        } else {
-            current_day
-        };
-
-        // Create new datetime with same time but next month
-        let next_datetime = datetime
-            .replace_year(next_year).ok().ok_or(Error::InvalidTimestamp)?
-            .replace_month(next_month).ok().ok_or(Error::InvalidTimestamp)?
-            .replace_day(next_day).ok().ok_or(Error::InvalidTimestamp)?;
+    // Save a new bill and add it to the due-date index
+    fn store_bill(env: &Env, bill: &Bill) {
+        let bill_key = DataKey::Bill(bill.id);
+        env.storage().persistent().set(&bill_key, bill);
+        Self::extend_ttl(env, &bill_key);
+        Self::index_due_bill(env, bill.id, bill.due_date);
+    }
 
-        Ok(next_datetime.unix_timestamp() as u64)
+    // Append a bill to its cycle's list
+    fn push_cycle_bill(env: &Env, cycle_id: u64, bill_id: u64) {
+        let cycle_bills_key = DataKey::CycleBills(cycle_id);
+        let mut cycle_bills: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&cycle_bills_key)
+            .unwrap_or(Vec::new(env));
+        cycle_bills.push_back(bill_id);
+        env.storage()
+            .persistent()
+            .set(&cycle_bills_key, &cycle_bills);
+        Self::extend_ttl(env, &cycle_bills_key);
     }
 
     // Validate that due date is at least 7 days in future
     fn validate_lead_time(env: &Env, due_date: u64) -> Result<(), Error> {
         let current_time = env.ledger().timestamp();
-        let min_lead_time = 7 * 24 * 60 * 60; // 7 days in seconds
+        let min_lead_time = MIN_LEAD_TIME_DAYS as u64 * 24 * 60 * 60; // 7 days in seconds
 
         if due_date < current_time + min_lead_time {
             return Err(Error::BillLeadTimeTooShort);
@@ -1048,7 +3399,7 @@ impl LockedIn {
         }
 
         let current_time = env.ledger().timestamp();
-        const SECONDS_IN_MONTH: u64 = 30 * 86400;
+        let next_month_timestamp = Self::add_months(current_time, 1)?;
 
         let mut bills_to_remove = Vec::new(&env);
 
@@ -1061,10 +3412,13 @@ impl LockedIn {
                 .ok_or(Error::BillNotFound)?;
 
             if bill.is_recurring {
-                bill.last_paid_date = Some(current_time + SECONDS_IN_MONTH);
+                bill.last_paid_date = Some(next_month_timestamp);
+                Self::skip_occurrence(&env, &mut bill, cycle.end_date)?;
                 env.storage().persistent().set(&bill_key, &bill);
                 Self::extend_ttl(&env, &bill_key);
             } else {
+                Self::unindex_due_bill(&env, bill_id, bill.due_date);
+                Self::drop_invoice(&env, bill_id);
                 bills_to_remove.push_back(bill_id);
             }
 
@@ -1173,6 +3527,10 @@ impl LockedIn {
                 new_bills.push_back(id);
             } else {
                 let bill_key = DataKey::Bill(id);
+                if let Some(bill) = env.storage().persistent().get::<DataKey, Bill>(&bill_key) {
+                    Self::unindex_due_bill(&env, id, bill.due_date);
+                }
+                Self::drop_invoice(&env, id);
                 env.storage().persistent().remove(&bill_key);
                 events::BillCancelled { bill_id: id }.publish(&env);
             }
@@ -1187,4 +3545,4 @@ impl LockedIn {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -2,9 +2,9 @@
 
 use super::*;
 use soroban_sdk::{
-    testutils::{Address as _, Ledger, LedgerInfo},
+    testutils::{Address as _, IssuerFlags, Ledger, LedgerInfo},
     token::{StellarAssetClient, TokenClient},
-    Address, Env, String, Vec,
+    Address, Bytes, Env, String, Vec,
 };
 
 // Test token setup helper
@@ -32,6 +32,27 @@ fn mint_tokens(env: &Env, token: &TokenClient, to: &Address, amount: i128) {
     stellar_asset.mint(to, &amount);
 }
 
+// Helper to propose a config change and execute it once the timelock has passed
+// A signer caller approves each step itself, which meets the default threshold of 1
+fn apply_config_change(
+    env: &Env,
+    client: &LockedInClient,
+    caller: &Address,
+    change: &ConfigChange,
+) {
+    let is_signer = client.admin_signers().contains(caller);
+    if is_signer {
+        client.approve_admin_action(caller, &AdminAction::ProposeConfigChange(change.clone()));
+    }
+    let proposal_id = client.propose_config_change(caller, change);
+    let proposal = client.get_proposal(&proposal_id);
+    set_ledger_time(env, env.ledger().timestamp(), proposal.executable_from);
+    if is_signer {
+        client.approve_admin_action(caller, &AdminAction::ExecuteConfigChange(proposal_id));
+    }
+    client.execute_config_change(caller, &proposal_id);
+}
+
 // Helper to set ledger time
 fn set_ledger_time(env: &Env, timestamp: u64, sequence: u32) {
     env.ledger().set(LedgerInfo {
@@ -55,24 +76,93 @@ fn test_initialization() {
     let (usdc_token, _) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
-    assert_eq!(client.admin(), admin);
+    assert_eq!(
+        client.admin_signers(),
+        Vec::from_array(&env, [admin.clone()])
+    );
+    assert_eq!(client.admin_threshold(), 1);
     assert_eq!(client.usdc_token(), usdc_token);
     assert_eq!(client.fee_recipient(), admin);
-    assert_eq!(client.fee_percentage(), 200); // 2% default
+    assert_eq!(client.fee_percentage(&usdc_token), 200); // 2% default
 }
 
 #[test]
 fn test_set_fee_percentage() {
     let env = Env::default();
     env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
 
     let admin = Address::generate(&env);
     let (usdc_token, _) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
-    client.set_fee_percentage(&500); // 5%
+    // The admin has no fee manager role, so each step needs the signers' approval
+    let change = ConfigChange::FeePercentage(usdc_token.clone(), 500); // 5%
+    client.approve_admin_action(&admin, &AdminAction::ProposeConfigChange(change.clone()));
+    let proposal_id = client.propose_config_change(&admin, &change);
+    assert_eq!(
+        client.get_pending_proposals(),
+        Vec::from_array(&env, [proposal_id])
+    );
+
+    // Nothing changes until the timelock has passed
+    let proposal = client.get_proposal(&proposal_id);
+    assert_eq!(proposal.change, change);
+    client.approve_admin_action(&admin, &AdminAction::ExecuteConfigChange(proposal_id));
+    assert_eq!(
+        client.try_execute_config_change(&admin, &proposal_id),
+        Err(Ok(Error::TimelockNotElapsed))
+    );
+    assert_eq!(client.fee_percentage(&usdc_token), 200);
+
+    set_ledger_time(&env, 1000, proposal.executable_from);
+    client.execute_config_change(&admin, &proposal_id);
+
+    assert_eq!(client.fee_percentage(&usdc_token), 500);
+    assert_eq!(client.get_pending_proposals().len(), 0);
+    assert_eq!(
+        client.try_get_proposal(&proposal_id),
+        Err(Ok(Error::ProposalNotFound))
+    );
+}
 
-    assert_eq!(client.fee_percentage(), 500);
+#[test]
+fn test_config_proposal_cancel_and_expiry() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let (usdc_token, _) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let change = ConfigChange::FeeRecipient(recipient.clone());
+    let propose = AdminAction::ProposeConfigChange(change.clone());
+    client.approve_admin_action(&admin, &propose);
+    let cancelled_id = client.propose_config_change(&admin, &change);
+    client.approve_admin_action(&admin, &propose);
+    let expired_id = client.propose_config_change(&admin, &change);
+
+    client.approve_admin_action(&admin, &AdminAction::CancelConfigChange(cancelled_id));
+    client.cancel_config_change(&admin, &cancelled_id);
+    assert_eq!(
+        client.get_pending_proposals(),
+        Vec::from_array(&env, [expired_id])
+    );
+    assert_eq!(
+        client.try_execute_config_change(&admin, &cancelled_id),
+        Err(Ok(Error::ProposalNotFound))
+    );
+
+    let proposal = client.get_proposal(&expired_id);
+    set_ledger_time(&env, 1000, proposal.expires_at + 1);
+    client.approve_admin_action(&admin, &AdminAction::ExecuteConfigChange(expired_id));
+    assert_eq!(
+        client.try_execute_config_change(&admin, &expired_id),
+        Err(Ok(Error::ProposalExpired))
+    );
+    assert_eq!(client.fee_recipient(), admin);
 }
 
 #[test]
@@ -85,7 +175,112 @@ fn test_set_fee_percentage_too_high() {
     let (usdc_token, _) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
-    client.set_fee_percentage(&501); // Over 5%
+    // Over 5%
+    let change = ConfigChange::FeePercentage(usdc_token, 501);
+    client.approve_admin_action(&admin, &AdminAction::ProposeConfigChange(change.clone()));
+    client.propose_config_change(&admin, &change);
+}
+
+#[test]
+fn test_keeper_and_fee_manager_roles() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let fee_manager = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    client.approve_admin_action(
+        &admin,
+        &AdminAction::GrantRole(Role::Keeper, keeper.clone()),
+    );
+    client.grant_role(&Role::Keeper, &keeper);
+    client.approve_admin_action(
+        &admin,
+        &AdminAction::GrantRole(Role::FeeManager, fee_manager.clone()),
+    );
+    client.grant_role(&Role::FeeManager, &fee_manager);
+
+    assert!(client.has_role(&Role::Keeper, &keeper));
+    assert!(!client.has_role(&Role::FeeManager, &keeper));
+    assert!(!client.has_role(&Role::Keeper, &admin));
+
+    // Fee manager can change fees but can't pay bills
+    apply_config_change(
+        &env,
+        &client,
+        &fee_manager,
+        &ConfigChange::FeePercentage(usdc_token.clone(), 300),
+    );
+    assert_eq!(client.fee_percentage(&usdc_token), 300);
+    assert_eq!(
+        client.try_propose_config_change(
+            &keeper,
+            &ConfigChange::FeePercentage(usdc_token.clone(), 400)
+        ),
+        Err(Ok(Error::Unauthorized))
+    );
+    assert_eq!(
+        client.try_propose_config_change(&fee_manager, &ConfigChange::UsdcToken(keeper.clone())),
+        Err(Ok(Error::Unauthorized))
+    );
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Water"),
+        10_000_000_000_000_000_000i128,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Utilities,
+        payee.clone(),
+    ));
+    bills.push_back((
+        String::from_str(&env, "Power"),
+        10_000_000_000_000_000_000i128,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Utilities,
+        payee.clone(),
+    ));
+    let bill_ids = client.add_bills(&cycle_id, &bills);
+
+    set_ledger_time(&env, due_date + 1, 100 + (17280 * 10));
+    assert_eq!(
+        client.try_admin_pay_bill(&fee_manager, &bill_ids.get(0).unwrap()),
+        Err(Ok(Error::Unauthorized))
+    );
+    client.admin_pay_bill(&keeper, &bill_ids.get(0).unwrap());
+
+    client.approve_admin_action(
+        &admin,
+        &AdminAction::RevokeRole(Role::Keeper, keeper.clone()),
+    );
+    client.revoke_role(&Role::Keeper, &keeper);
+    assert!(!client.has_role(&Role::Keeper, &keeper));
+    assert_eq!(
+        client.try_admin_pay_bill(&keeper, &bill_ids.get(1).unwrap()),
+        Err(Ok(Error::Unauthorized))
+    );
+    client.approve_admin_action(
+        &admin,
+        &AdminAction::RevokeRole(Role::Keeper, keeper.clone()),
+    );
+    assert_eq!(
+        client.try_revoke_role(&Role::Keeper, &keeper),
+        Err(Ok(Error::RoleNotGranted))
+    );
 }
 
 #[test]
@@ -102,7 +297,7 @@ fn test_create_cycle() {
     let amount = 100_000_000_000_000_000_000i128; // 100 USDC
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     assert_eq!(cycle_id, 1);
 
@@ -128,7 +323,7 @@ fn test_create_cycle_invalid_duration_zero() {
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    client.create_cycle(&user, &0, &amount); // 0 months
+    client.create_cycle(&user, &usdc_token, &0, &amount); // 0 months
 }
 
 #[test]
@@ -146,7 +341,7 @@ fn test_create_cycle_invalid_duration_too_long() {
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    client.create_cycle(&user, &13, &amount); // 13 months
+    client.create_cycle(&user, &usdc_token, &13, &amount); // 13 months
 }
 
 #[test]
@@ -163,11 +358,11 @@ fn test_get_user_cycles() {
     let amount = 50_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount * 3);
 
-    let id1 = client.create_cycle(&user, &3, &amount);
-    let id2 = client.create_cycle(&user, &6, &amount);
-    let id3 = client.create_cycle(&user, &12, &amount);
+    let id1 = client.create_cycle(&user, &usdc_token, &3, &amount);
+    let id2 = client.create_cycle(&user, &usdc_token, &6, &amount);
+    let id3 = client.create_cycle(&user, &usdc_token, &12, &amount);
 
-    let user_cycles = client.get_user_cycles(&user);
+    let user_cycles = client.get_user_cycles(&user, &None);
 
     assert_eq!(user_cycles.len(), 3);
     assert_eq!(user_cycles.get(0).unwrap(), id1);
@@ -175,6 +370,130 @@ fn test_get_user_cycles() {
     assert_eq!(user_cycles.get(2).unwrap(), id3);
 }
 
+#[test]
+fn test_multi_token_cycles() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let (eurc_token, eurc) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 50_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+    mint_tokens(&env, &eurc, &user, amount * 2);
+
+    assert_eq!(
+        client.allowed_tokens(),
+        Vec::from_array(&env, [usdc_token.clone()])
+    );
+    assert_eq!(
+        client.try_create_cycle(&user, &eurc_token, &3, &amount),
+        Err(Ok(Error::TokenNotAllowed))
+    );
+
+    client.approve_admin_action(&admin, &AdminAction::AllowToken(eurc_token.clone(), 400));
+    client.allow_token(&eurc_token, &400);
+    assert_eq!(client.fee_percentage(&eurc_token), 400);
+    assert_eq!(client.fee_percentage(&usdc_token), 200);
+
+    let usdc_cycle = client.create_cycle(&user, &usdc_token, &3, &amount);
+    let eurc_cycle = client.create_cycle(&user, &eurc_token, &3, &amount);
+
+    // Each cycle is charged the fee of its own token, in that token
+    assert_eq!(client.get_cycle(&eurc_cycle).fee_percentage, 400);
+    assert_eq!(eurc.balance(&admin), amount * 400 / 10000);
+    assert_eq!(token.balance(&admin), amount * 200 / 10000);
+    assert_eq!(eurc.balance(&client.address), amount - amount * 400 / 10000);
+
+    assert_eq!(
+        client.get_user_cycles(&user, &Some(eurc_token.clone())),
+        Vec::from_array(&env, [eurc_cycle])
+    );
+    assert_eq!(
+        client.get_user_cycles(&user, &Some(usdc_token.clone())),
+        Vec::from_array(&env, [usdc_cycle])
+    );
+
+    // Disallowing stops new cycles but the existing one still ends in its token
+    client.approve_admin_action(&admin, &AdminAction::DisallowToken(eurc_token.clone()));
+    client.disallow_token(&eurc_token);
+    assert_eq!(
+        client.try_create_cycle(&user, &eurc_token, &3, &amount),
+        Err(Ok(Error::TokenNotAllowed))
+    );
+    client.approve_admin_action(&admin, &AdminAction::DisallowToken(eurc_token.clone()));
+    assert_eq!(
+        client.try_disallow_token(&eurc_token),
+        Err(Ok(Error::TokenNotAllowed))
+    );
+
+    // The default token stays allowed while it is the default
+    client.approve_admin_action(&admin, &AdminAction::DisallowToken(usdc_token.clone()));
+    assert_eq!(
+        client.try_disallow_token(&usdc_token),
+        Err(Ok(Error::DefaultTokenNotRemovable))
+    );
+    assert_eq!(
+        client.allowed_tokens(),
+        Vec::from_array(&env, [usdc_token.clone()])
+    );
+
+    client.approve_admin_action(&admin, &AdminAction::EndCycle(eurc_cycle));
+    client.admin_end_cycle(&eurc_cycle);
+    assert_eq!(eurc.balance(&user), amount * 2 - amount * 400 / 10000);
+    assert_eq!(eurc.balance(&client.address), 0);
+}
+
+#[test]
+fn test_deposit_to_cycle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    let top_up = 50_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount + top_up);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    // 120 USDC doesn't fit in the 98 available
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Rent"),
+        120_000_000_000_000_000_000i128,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Housing,
+        payee.clone(),
+    ));
+    assert_eq!(
+        client.try_add_bills(&cycle_id, &bills),
+        Err(Ok(Error::InsufficientFunds))
+    );
+
+    client.deposit_to_cycle(&cycle_id, &top_up);
+
+    let cycle = client.get_cycle(&cycle_id);
+    assert_eq!(cycle.total_deposited, amount + top_up);
+    assert_eq!(cycle.operating_fee, (amount + top_up) * 200 / 10000);
+    assert_eq!(token.balance(&admin), (amount + top_up) * 200 / 10000);
+
+    // 147 USDC now available
+    client.add_bills(&cycle_id, &bills);
+}
+
 #[test]
 fn test_add_bill() {
     let env = Env::default();
@@ -183,13 +502,14 @@ fn test_add_bill() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     let bill_name = String::from_str(&env, "Electricity");
     let bill_amount = 20_000_000_000_000_000_000i128;
@@ -204,6 +524,7 @@ fn test_add_bill() {
         false,
         recurrence_calendar.clone(),
         BillCategory::Other,
+        payee.clone(),
     ));
     let bill_ids = client.add_bills(&cycle_id, &bills);
     let bill_id = bill_ids.get(0).unwrap();
@@ -224,13 +545,14 @@ fn test_pay_bill() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     let bill_amount = 10_000_000_000_000_000_000i128;
     let due_date = 1000 + (10 * 24 * 60 * 60); // 10 days
@@ -243,6 +565,7 @@ fn test_pay_bill() {
         false,
         Vec::new(&env),
         BillCategory::Other,
+        payee.clone(),
     ));
     let bill_ids = client.add_bills(&cycle_id, &bills);
     let bill_id = bill_ids.get(0).unwrap();
@@ -253,6 +576,42 @@ fn test_pay_bill() {
 
     let bill = client.get_bill(&bill_id);
     assert_eq!(bill.is_paid, true);
+    assert_eq!(bill.payee, payee);
+
+    // Funds go to the payee, not back to the cycle owner
+    assert_eq!(token.balance(&payee), bill_amount);
+    assert_eq!(token.balance(&user), 0);
+}
+
+#[test]
+#[should_panic(expected = "#51")]
+fn test_add_bill_payee_cannot_be_contract() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Water"),
+        10_000_000_000_000_000_000i128,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Other,
+        client.address.clone(),
+    ));
+    client.add_bills(&cycle_id, &bills);
 }
 
 #[test]
@@ -264,13 +623,54 @@ fn test_pay_bill_not_due() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Water"),
+        10_000_000_000_000_000_000i128,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Other,
+        payee.clone(),
+    ));
+    let bill_ids = client.add_bills(&cycle_id, &bills);
+    let bill_id = bill_ids.get(0).unwrap();
+
+    client.pay_bill(&bill_id);
+}
+
+#[test]
+fn test_pay_bill_within_payment_window() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    client.approve_admin_action(&admin, &AdminAction::SetPaymentWindow(2, 3));
+    client.set_payment_window(&2, &3);
+    let window = client.payment_window();
+    assert_eq!(window.days_before, 2);
+    assert_eq!(window.days_after, 3);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     let due_date = 1000 + (10 * 24 * 60 * 60);
     let mut bills = Vec::new(&env);
@@ -280,56 +680,1120 @@ fn test_pay_bill_not_due() {
         due_date,
         false,
         Vec::new(&env),
-        BillCategory::Other,
+        BillCategory::Utilities,
+        payee.clone(),
+    ));
+    bills.push_back((
+        String::from_str(&env, "Power"),
+        10_000_000_000_000_000_000i128,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Utilities,
+        payee.clone(),
+    ));
+    let bill_ids = client.add_bills(&cycle_id, &bills);
+
+    set_ledger_time(&env, due_date - (3 * 24 * 60 * 60), 100 + (17280 * 7));
+    assert_eq!(
+        client.try_pay_bill(&bill_ids.get(0).unwrap()),
+        Err(Ok(Error::BillNotDueYet))
+    );
+
+    // Two days early is inside the window
+    set_ledger_time(&env, due_date - (2 * 24 * 60 * 60), 100 + (17280 * 8));
+    client.pay_bill(&bill_ids.get(0).unwrap());
+
+    // Well past the grace period the bill is late but still payable
+    set_ledger_time(&env, due_date + (20 * 24 * 60 * 60), 100 + (17280 * 30));
+    client.pay_bill(&bill_ids.get(1).unwrap());
+
+    assert_eq!(client.get_bill(&bill_ids.get(1).unwrap()).is_paid, true);
+    assert_eq!(token.balance(&payee), 20_000_000_000_000_000_000i128);
+}
+
+#[test]
+#[should_panic(expected = "#28")]
+fn test_set_payment_window_too_early() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let (usdc_token, _) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    client.approve_admin_action(&admin, &AdminAction::SetPaymentWindow(7, 3));
+    client.set_payment_window(&7, &3);
+}
+
+#[test]
+fn test_get_due_bills() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    let day_10 = 1000 + (10 * 24 * 60 * 60);
+    let day_15 = 1000 + (15 * 24 * 60 * 60);
+
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Water"),
+        5_000_000_000_000_000_000i128,
+        day_10,
+        false,
+        Vec::new(&env),
+        BillCategory::Utilities,
+        payee.clone(),
+    ));
+    bills.push_back((
+        String::from_str(&env, "Power"),
+        5_000_000_000_000_000_000i128,
+        day_10,
+        false,
+        Vec::new(&env),
+        BillCategory::Utilities,
+        payee.clone(),
+    ));
+    bills.push_back((
+        String::from_str(&env, "Rent"),
+        20_000_000_000_000_000_000i128,
+        day_15,
+        true,
+        Vec::new(&env),
+        BillCategory::Housing,
+        payee.clone(),
+    ));
+    let bill_ids = client.add_bills(&cycle_id, &bills);
+    let water = bill_ids.get(0).unwrap();
+    let power = bill_ids.get(1).unwrap();
+    let rent = bill_ids.get(2).unwrap();
+
+    assert_eq!(
+        client.get_due_bills(&day_10, &day_10, &0, &10),
+        Vec::from_array(&env, [water, power])
+    );
+    assert_eq!(
+        client.get_due_bills(&day_10, &day_15, &0, &10),
+        Vec::from_array(&env, [water, power, rent])
+    );
+
+    // Paginated
+    assert_eq!(
+        client.get_due_bills(&day_10, &day_15, &0, &2),
+        Vec::from_array(&env, [water, power])
+    );
+    assert_eq!(
+        client.get_due_bills(&day_10, &day_15, &2, &2),
+        Vec::from_array(&env, [rent])
+    );
+
+    // Paid bills leave the index, recurring ones move to their next due date
+    set_ledger_time(&env, day_10 + 1, 100 + (17280 * 10));
+    client.approve_admin_action(&admin, &AdminAction::PayBill(water));
+    client.admin_pay_bill(&admin, &water);
+    set_ledger_time(&env, day_15 + 1, 100 + (17280 * 15));
+    client.approve_admin_action(&admin, &AdminAction::PayBill(rent));
+    client.admin_pay_bill(&admin, &rent);
+
+    assert_eq!(
+        client.get_due_bills(&day_10, &day_15, &0, &10),
+        Vec::from_array(&env, [power])
+    );
+    let next_rent = client.get_bill(&rent).due_date;
+    assert_eq!(
+        client.get_due_bills(&next_rent, &next_rent, &0, &10),
+        Vec::from_array(&env, [rent])
+    );
+
+    // Ending the cycle clears its outstanding bills
+    client.approve_admin_action(&admin, &AdminAction::EndCycle(cycle_id));
+    client.admin_end_cycle(&cycle_id);
+    assert_eq!(client.get_due_bills(&day_10, &day_15, &0, &10).len(), 0);
+    assert_eq!(
+        client.get_due_bills(&next_rent, &next_rent, &0, &10).len(),
+        0
+    );
+}
+
+#[test]
+fn test_admin_pay_bills_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    // Revocable so a payee can be deauthorized to make its transfer fail
+    let stellar_asset = env.register_stellar_asset_contract_v2(admin.clone());
+    stellar_asset.issuer().set_flag(IssuerFlags::RevocableFlag);
+    let usdc_token = stellar_asset.address();
+    let token = TokenClient::new(&env, &usdc_token);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    for name in ["Water", "Power", "Internet"] {
+        bills.push_back((
+            String::from_str(&env, name),
+            5_000_000_000_000_000_000i128,
+            due_date,
+            false,
+            Vec::new(&env),
+            BillCategory::Utilities,
+            payee.clone(),
+        ));
+    }
+    // Gas is indexed ahead of the others and its payee can't receive the token
+    let gas_payee = Address::generate(&env);
+    let gas = client
+        .add_bills(
+            &cycle_id,
+            &Vec::from_array(
+                &env,
+                [(
+                    String::from_str(&env, "Gas"),
+                    5_000_000_000_000_000_000i128,
+                    due_date,
+                    false,
+                    Vec::new(&env),
+                    BillCategory::Utilities,
+                    gas_payee.clone(),
+                )],
+            ),
+        )
+        .get(0)
+        .unwrap();
+    StellarAssetClient::new(&env, &usdc_token).set_authorized(&gas_payee, &false);
+
+    let bill_ids = client.add_bills(&cycle_id, &bills);
+    let water = bill_ids.get(0).unwrap();
+    let power = bill_ids.get(1).unwrap();
+    let internet = bill_ids.get(2).unwrap();
+
+    set_ledger_time(&env, due_date + 1, 100 + (17280 * 10));
+    client.approve_admin_action(&admin, &AdminAction::PayBill(water));
+    client.admin_pay_bill(&admin, &water);
+
+    // Water is already paid and bill 99 doesn't exist, the rest still go through
+    let batch = Vec::from_array(&env, [water, power, 99]);
+    client.approve_admin_action(&admin, &AdminAction::PayBills(batch.clone()));
+    let results = client.admin_pay_bills(&admin, &batch);
+    assert_eq!(results.len(), 3);
+    assert_eq!(
+        results.get(0).unwrap(),
+        PaymentResult {
+            bill_id: water,
+            paid: false,
+            error: Some(Error::BillAlreadyPaid as u32),
+        }
+    );
+    assert_eq!(results.get(1).unwrap().paid, true);
+    assert_eq!(
+        results.get(2).unwrap().error,
+        Some(Error::BillNotFound as u32)
+    );
+
+    // The failed transfer is reported and gas stays due, the cursor moves past it
+    client.approve_admin_action(&admin, &AdminAction::PayDueBills(due_date, 0, 1));
+    let results = client.pay_due_bills(&admin, &due_date, &0, &1);
+    assert_eq!(
+        results,
+        Vec::from_array(
+            &env,
+            [PaymentResult {
+                bill_id: gas,
+                paid: false,
+                error: Some(Error::TokenTransferFailed as u32),
+            }]
+        )
+    );
+
+    client.approve_admin_action(&admin, &AdminAction::PayDueBills(due_date, 1, 10));
+    let results = client.pay_due_bills(&admin, &due_date, &1, &10);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results.get(0).unwrap().bill_id, internet);
+    assert_eq!(results.get(0).unwrap().paid, true);
+
+    assert_eq!(token.balance(&payee), 15_000_000_000_000_000_000i128);
+    assert_eq!(client.get_bill(&gas).is_paid, false);
+    assert_eq!(
+        client.get_due_bills(&due_date, &due_date, &0, &10),
+        Vec::from_array(&env, [gas])
+    );
+}
+
+#[test]
+fn test_execute_due_bill_pays_keeper_tip() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    // Half of each 2% fee funds tips of 0.5 USDC
+    let tip = 500_000_000_000_000_000i128;
+    client.approve_admin_action(&admin, &AdminAction::SetKeeperTip(tip, 5000));
+    client.set_keeper_tip(&admin, &tip, &5000);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    let fee = amount * 200 / 10000;
+    assert_eq!(client.get_cycle(&cycle_id).keeper_budget, fee / 2);
+    assert_eq!(token.balance(&admin), fee / 2);
+
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let bill_amount = 10_000_000_000_000_000_000i128;
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Water"),
+        bill_amount,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Utilities,
+        payee.clone(),
+    ));
+    let bill_ids = client.add_bills(&cycle_id, &bills);
+    let bill_id = bill_ids.get(0).unwrap();
+
+    // Same timing rules as pay_bill
+    assert_eq!(
+        client.try_execute_due_bill(&keeper, &bill_id),
+        Err(Ok(Error::BillNotDueYet))
+    );
+
+    set_ledger_time(&env, due_date + 1, 100 + (17280 * 10));
+    assert_eq!(client.execute_due_bill(&keeper, &bill_id), tip);
+    assert_eq!(token.balance(&keeper), tip);
+    assert_eq!(token.balance(&payee), bill_amount);
+
+    assert_eq!(
+        client.try_execute_due_bill(&keeper, &bill_id),
+        Err(Ok(Error::BillAlreadyPaid))
+    );
+
+    // Leftover budget goes to the fee recipient, the user's surplus is unaffected
+    client.approve_admin_action(&admin, &AdminAction::EndCycle(cycle_id));
+    client.admin_end_cycle(&cycle_id);
+    assert_eq!(token.balance(&admin), fee - tip);
+    assert_eq!(token.balance(&user), amount - fee - bill_amount);
+    assert_eq!(token.balance(&client.address), 0);
+}
+
+#[test]
+fn test_end_cycle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    set_ledger_time(&env, 1000 + (91 * 24 * 60 * 60), 100 + (17280 * 91));
+
+    client.end_cycle(&cycle_id);
+
+    let cycle = client.get_cycle(&cycle_id);
+    assert_eq!(cycle.is_active, false);
+}
+
+#[test]
+#[should_panic(expected = "#30")]
+fn test_end_cycle_too_early() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    client.end_cycle(&cycle_id);
+}
+
+#[test]
+fn test_get_cycle_summary() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    let rent_amount = 10_000_000_000_000_000_000i128;
+    let course_amount = 5_000_000_000_000_000_000i128;
+    let rent_due = 1000 + (10 * 24 * 60 * 60);
+    let course_due = 1000 + (19 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Rent"),
+        rent_amount,
+        rent_due,
+        true,
+        Vec::new(&env),
+        BillCategory::Housing,
+        payee.clone(),
+    ));
+    bills.push_back((
+        String::from_str(&env, "Course"),
+        course_amount,
+        course_due,
+        false,
+        Vec::new(&env),
+        BillCategory::Education,
+        payee.clone(),
+    ));
+    let bill_ids = client.add_bills(&cycle_id, &bills);
+
+    // Rent is due in January, February and March
+    let fee = amount * 200 / 10000;
+    let summary = client.get_cycle_summary(&cycle_id);
+    assert_eq!(summary.total_deposited, amount);
+    assert_eq!(summary.operating_fee, fee);
+    assert_eq!(summary.reserved, rent_amount * 3 + course_amount);
+    assert_eq!(summary.disbursed, 0);
+    assert_eq!(
+        summary.free_balance,
+        amount - fee - rent_amount * 3 - course_amount
+    );
+    assert_eq!(summary.next_due_date, Some(rent_due));
+    assert_eq!(summary.pending_bills, 2);
+
+    set_ledger_time(&env, rent_due, 100 + (17280 * 10));
+    client.pay_bill(&bill_ids.get(0).unwrap());
+
+    // Past the course's 3-day grace period
+    set_ledger_time(&env, course_due + (4 * 24 * 60 * 60), 100 + (17280 * 23));
+    let paid_summary = client.get_cycle_summary(&cycle_id);
+    assert_eq!(paid_summary.reserved, rent_amount * 2 + course_amount);
+    assert_eq!(paid_summary.disbursed, rent_amount);
+    assert_eq!(paid_summary.free_balance, summary.free_balance);
+    assert_eq!(paid_summary.next_due_date, Some(course_due));
+    assert_eq!(paid_summary.pending_bills, 1);
+    assert_eq!(paid_summary.overdue_bills, 1);
+    assert_eq!(paid_summary.paid_bills, 0);
+
+    // The whole free balance can be reserved
+    let due_date = 1000 + (40 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Laptop"),
+        paid_summary.free_balance,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Other,
+        payee.clone(),
+    ));
+    client.add_bills(&cycle_id, &bills);
+    assert_eq!(client.get_cycle_summary(&cycle_id).free_balance, 0);
+}
+
+#[test]
+fn test_check_solvency() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let keeper = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    // Nothing is owed before the first cycle
+    assert_eq!(client.check_solvency().len(), 0);
+
+    // Keep part of each fee in the vault so the keeper budget is counted too
+    let tip = 500_000_000_000_000_000i128;
+    client.approve_admin_action(&admin, &AdminAction::SetKeeperTip(tip, 5000));
+    client.set_keeper_tip(&admin, &tip, &5000);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount * 2);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+    client.deposit_to_cycle(&cycle_id, &amount);
+
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Water"),
+        10_000_000_000_000_000_000i128,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Utilities,
+        payee.clone(),
+    ));
+    let bill_id = client.add_bills(&cycle_id, &bills).get(0).unwrap();
+
+    let assert_solvent = |client: &LockedInClient| {
+        let report = client.check_solvency().get(0).unwrap();
+        assert_eq!(report.token, usdc_token);
+        assert_eq!(report.liabilities, token.balance(&client.address));
+        assert_eq!(report.shortfall, 0);
+    };
+    assert_solvent(&client);
+
+    set_ledger_time(&env, due_date, 100 + (17280 * 10));
+    client.execute_due_bill(&keeper, &bill_id);
+    assert_solvent(&client);
+
+    client.withdraw_early(&cycle_id, &10_000_000_000_000_000_000i128);
+    assert_solvent(&client);
+
+    // Money leaving the vault outside the contract's books shows up as a shortfall
+    let missing = 1_000_000_000_000_000_000i128;
+    env.as_contract(&client.address, || {
+        token.transfer(&client.address, &admin, &missing);
+    });
+    let report = client.check_solvency().get(0).unwrap();
+    assert_eq!(report.shortfall, missing);
+    assert_eq!(report.balance + missing, report.liabilities);
+
+    // Ended cycles owe nothing
+    mint_tokens(&env, &token, &client.address, missing);
+    client.approve_admin_action(&admin, &AdminAction::EndCycle(cycle_id));
+    client.admin_end_cycle(&cycle_id);
+    let report = client.check_solvency().get(0).unwrap();
+    assert_eq!(report.liabilities, 0);
+}
+
+#[test]
+fn test_installment_bill() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let lender = Address::generate(&env);
+    let insurer = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    // 100 in 3 installments: 34, 34 and a final 32, all inside the cycle
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let loan_id = client.add_installment_bill(
+        &cycle_id,
+        &String::from_str(&env, "Loan"),
+        &InstallmentPlan {
+            total_amount: 100,
+            installment_count: 3,
+        },
+        &due_date,
+        &BillCategory::Debt,
+        &lender,
+    );
+    assert_eq!(client.get_bill(&loan_id).amount, 34);
+
+    // Only three of the four premium installments fall before the cycle ends
+    let premium_total = 40_000_000_000_000_000_000i128;
+    let premium_id = client.add_installment_bill(
+        &cycle_id,
+        &String::from_str(&env, "Insurance"),
+        &InstallmentPlan {
+            total_amount: premium_total,
+            installment_count: 4,
+        },
+        &due_date,
+        &BillCategory::Insurance,
+        &insurer,
+    );
+    assert_eq!(
+        client.get_cycle_summary(&cycle_id).reserved,
+        100 + premium_total / 4 * 3
+    );
+
+    assert_eq!(
+        client.try_add_installment_bill(
+            &cycle_id,
+            &String::from_str(&env, "Empty"),
+            &InstallmentPlan {
+                total_amount: 100,
+                installment_count: 0,
+            },
+            &due_date,
+            &BillCategory::Debt,
+            &lender,
+        ),
+        Err(Ok(Error::InvalidBillAmount))
+    );
+
+    let mut due = due_date;
+    for (month, expected) in [(1u32, 34i128), (2, 34), (3, 32)] {
+        set_ledger_time(&env, due, 100 + (17280 * 31 * month));
+        client.pay_bill(&loan_id);
+        client.pay_bill(&premium_id);
+
+        let loan = client.get_bill(&loan_id);
+        assert_eq!(loan.installments_paid, month);
+        assert_eq!(token.balance(&lender), 34 * (month as i128 - 1) + expected);
+        due = loan.due_date;
+    }
+
+    // The loan is paid off; the premium still has one installment left for the next cycle
+    let loan = client.get_bill(&loan_id);
+    assert_eq!(loan.remaining_balance, 0);
+    assert_eq!(loan.is_paid, true);
+    let premium = client.get_bill(&premium_id);
+    assert_eq!(premium.installments_paid, 3);
+    assert_eq!(premium.remaining_balance, premium_total / 4);
+    assert_eq!(token.balance(&insurer), premium_total / 4 * 3);
+    assert_eq!(
+        client.get_cycle(&cycle_id).total_disbursed,
+        100 + premium_total / 4 * 3
+    );
+}
+
+#[test]
+fn test_variable_bill() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let utility = Address::generate(&env);
+    let stranger = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    // Up to 20 a month is reserved for January, February and March
+    let max_amount = 20_000_000_000_000_000_000i128;
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let bill_id = client.add_variable_bill(
+        &cycle_id,
+        &String::from_str(&env, "Electricity"),
+        &max_amount,
+        &due_date,
+        &Vec::new(&env),
+        &BillCategory::Utilities,
+        &utility,
+    );
+    let summary = client.get_cycle_summary(&cycle_id);
+    assert_eq!(summary.reserved, max_amount * 3);
+
+    set_ledger_time(&env, due_date, 100 + (17280 * 10));
+    assert_eq!(
+        client.try_pay_bill(&bill_id),
+        Err(Ok(Error::BillAmountNotSet))
+    );
+
+    // The payee sets this month's figure, never above the cap
+    assert_eq!(
+        client.try_set_bill_amount(&utility, &bill_id, &(max_amount + 1)),
+        Err(Ok(Error::InvalidBillAmount))
+    );
+    assert_eq!(
+        client.try_set_bill_amount(&stranger, &bill_id, &1),
+        Err(Ok(Error::Unauthorized))
+    );
+    let january = 12_000_000_000_000_000_000i128;
+    client.set_bill_amount(&utility, &bill_id, &january);
+    client.pay_bill(&bill_id);
+    assert_eq!(token.balance(&utility), january);
+
+    // The unused part of January's reservation is free again
+    let bill = client.get_bill(&bill_id);
+    assert_eq!(bill.actual_amount, None);
+    assert_eq!(
+        client.get_cycle_summary(&cycle_id).free_balance,
+        summary.free_balance + max_amount - january
+    );
+
+    // The owner can set it too
+    let february = 15_000_000_000_000_000_000i128;
+    client.set_bill_amount(&user, &bill_id, &february);
+    set_ledger_time(&env, bill.due_date, 100 + (17280 * 41));
+    client.pay_bill(&bill_id);
+    assert_eq!(token.balance(&utility), january + february);
+    assert_eq!(
+        client.get_cycle(&cycle_id).total_disbursed,
+        january + february
+    );
+}
+
+#[test]
+fn test_payee_invoice() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let landlord = Address::generate(&env);
+    let lender = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    let rent = 30_000_000_000_000_000_000i128;
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Rent"),
+        rent,
+        due_date,
+        true,
+        Vec::new(&env),
+        BillCategory::Housing,
+        landlord.clone(),
+    ));
+    let bill_id = client.add_bills(&cycle_id, &bills).get(0).unwrap();
+
+    // 100 owed in two installments of 50
+    let loan_id = client.add_installment_bill(
+        &cycle_id,
+        &String::from_str(&env, "Loan"),
+        &InstallmentPlan {
+            total_amount: 100,
+            installment_count: 2,
+        },
+        &due_date,
+        &BillCategory::Debt,
+        &lender,
+    );
+    let loan_reference = String::from_str(&env, "LOAN-1");
+    client.submit_invoice(&loan_id, &10, &loan_reference);
+
+    // Invoices can't ask for more than the bill reserves
+    let reference = String::from_str(&env, "INV-001");
+    assert_eq!(
+        client.try_submit_invoice(&bill_id, &(rent + 1), &reference),
+        Err(Ok(Error::InvalidBillAmount))
+    );
+
+    let invoiced = 25_000_000_000_000_000_000i128;
+    client.submit_invoice(&bill_id, &invoiced, &reference);
+    assert_eq!(
+        client.try_submit_invoice(&bill_id, &invoiced, &reference),
+        Err(Ok(Error::InvoiceAlreadyPending))
+    );
+
+    // The owner rejects it and the payee sends a corrected one
+    client.reject_invoice(&bill_id);
+    assert_eq!(
+        client.try_get_invoice(&bill_id),
+        Err(Ok(Error::InvoiceNotFound))
+    );
+    let reference = String::from_str(&env, "INV-002");
+    client.submit_invoice(&bill_id, &invoiced, &reference);
+
+    let invoices = client.get_pending_invoices(&cycle_id);
+    assert_eq!(invoices.len(), 2);
+    assert_eq!(invoices.get(0).unwrap().reference, reference);
+    assert_eq!(invoices.get(0).unwrap().due_date, due_date);
+    assert_eq!(invoices.get(1).unwrap().bill_id, loan_id);
+
+    client.approve_invoice(&bill_id);
+    assert_eq!(
+        client.try_approve_invoice(&bill_id),
+        Err(Ok(Error::InvoiceAlreadyApproved))
+    );
+
+    // Renaming the bill leaves the occurrence, and its invoice, where they were
+    client.update_bill(
+        &bill_id,
+        &String::from_str(&env, "Flat rent"),
+        &rent,
+        &due_date,
+        &BillCategory::Housing,
+    );
+    assert_eq!(client.get_invoice(&bill_id).amount, invoiced);
+
+    // Payment settles the invoiced amount and closes the invoice
+    set_ledger_time(&env, due_date, 100 + (17280 * 10));
+    client.pay_bill(&bill_id);
+
+    // A pending invoice holds the payment until the owner decides
+    assert_eq!(
+        client.try_pay_bill(&loan_id),
+        Err(Ok(Error::InvoicePendingApproval))
+    );
+    client.approve_invoice(&loan_id);
+    client.pay_bill(&loan_id);
+    assert_eq!(token.balance(&landlord), invoiced);
+    assert_eq!(
+        client.try_get_invoice(&bill_id),
+        Err(Ok(Error::InvoiceNotFound))
+    );
+
+    // Without an invoice the next occurrence pays the bill amount
+    let next_due = client.get_bill(&bill_id).due_date;
+    set_ledger_time(&env, next_due, 100 + (17280 * 41));
+    client.pay_bill(&bill_id);
+    assert_eq!(token.balance(&landlord), invoiced + rent);
+
+    // Two installments settled for 10 each leave 80 of the plan owed and reserved
+    client.submit_invoice(&loan_id, &10, &loan_reference);
+    client.approve_invoice(&loan_id);
+    client.pay_bill(&loan_id);
+    let loan = client.get_bill(&loan_id);
+    assert_eq!(loan.installments_paid, 2);
+    assert_eq!(loan.remaining_balance, 80);
+    assert_eq!(loan.is_paid, false);
+    assert_eq!(client.get_cycle_summary(&cycle_id).reserved, rent + 50);
+
+    // The next payment is a full installment, still within the balance
+    set_ledger_time(&env, loan.due_date, 100 + (17280 * 72));
+    client.pay_bill(&loan_id);
+    assert_eq!(token.balance(&lender), 70);
+    assert_eq!(client.get_bill(&loan_id).remaining_balance, 30);
+    assert_eq!(client.get_cycle_summary(&cycle_id).reserved, rent);
+}
+
+#[test]
+fn test_renew_cycle() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    let extra_deposit = 10_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount + extra_deposit);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    let bill_amount = 10_000_000_000_000_000_000i128;
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Rent"),
+        bill_amount,
+        1000 + (10 * 24 * 60 * 60), // Jan 11
+        true,
+        Vec::new(&env),
+        BillCategory::Housing,
+        payee.clone(),
+    ));
+    bills.push_back((
+        String::from_str(&env, "Course"),
+        bill_amount,
+        1000 + (19 * 24 * 60 * 60),
+        false,
+        Vec::new(&env),
+        BillCategory::Education,
+        payee.clone(),
+    ));
+    client.add_bills(&cycle_id, &bills);
+
+    assert_eq!(
+        client.try_renew_cycle(&cycle_id, &2, &extra_deposit),
+        Err(Ok(Error::CycleNotEnded))
+    );
+
+    // April 2nd, a day after the cycle ended
+    set_ledger_time(&env, 1000 + (91 * 24 * 60 * 60), 100 + (17280 * 91));
+    let new_cycle_id = client.renew_cycle(&cycle_id, &2, &extra_deposit);
+
+    // Nothing went back to the wallet; the surplus moved into the new cycle
+    let fee = amount * 200 / 10000;
+    let surplus = amount - fee;
+    assert_eq!(token.balance(&user), 0);
+
+    let old_cycle = client.get_cycle(&cycle_id);
+    assert_eq!(old_cycle.is_active, false);
+    assert_eq!(old_cycle.next_cycle_id, Some(new_cycle_id));
+
+    let new_cycle = client.get_cycle(&new_cycle_id);
+    assert_eq!(new_cycle.is_active, true);
+    assert_eq!(new_cycle.previous_cycle_id, Some(cycle_id));
+    assert_eq!(new_cycle.total_deposited, surplus + extra_deposit);
+
+    // Only the recurring bill carries over, due on the same day of the month
+    let new_bills = client.get_cycle_bills(&new_cycle_id);
+    assert_eq!(new_bills.len(), 1);
+    let rent = client.get_bill(&new_bills.get(0).unwrap());
+    assert_eq!(rent.name, String::from_str(&env, "Rent"));
+    assert_eq!(rent.due_date, 1000 + (100 * 24 * 60 * 60)); // April 11
+    assert_eq!(rent.is_paid, false);
+
+    assert_eq!(
+        client.try_renew_cycle(&cycle_id, &2, &0),
+        Err(Ok(Error::CycleAlreadyEnded))
+    );
+
+    // With auto-renew on, anyone ending the cycle renews it for the same two months
+    client.set_auto_renew(&new_cycle_id, &true);
+    set_ledger_time(&env, 1000 + (153 * 24 * 60 * 60), 100 + (17280 * 153));
+    client.end_cycle(&new_cycle_id);
+
+    let renewed_id = client.get_cycle(&new_cycle_id).next_cycle_id.unwrap();
+    let renewed = client.get_cycle(&renewed_id);
+    assert_eq!(renewed.auto_renew, true);
+    assert_eq!(renewed.previous_cycle_id, Some(new_cycle_id));
+    assert_eq!(renewed.end_date, 1000 + (214 * 24 * 60 * 60)); // August 3
+    assert_eq!(token.balance(&user), 0);
+}
+
+#[test]
+fn test_pause_and_emergency_withdraw() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    let bill_amount = 30_000_000_000_000_000_000i128;
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Rent"),
+        bill_amount,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Housing,
+        payee.clone(),
     ));
-    let bill_ids = client.add_bills(&cycle_id, &bills);
-    let bill_id = bill_ids.get(0).unwrap();
-
+    let bill_id = client.add_bills(&cycle_id, &bills).get(0).unwrap();
+
+    // Nothing is paused yet
+    assert_eq!(
+        client.try_emergency_withdraw_own_cycle(&cycle_id),
+        Err(Ok(Error::NotPaused))
+    );
+
+    client.approve_admin_action(&admin, &AdminAction::Pause(PauseScope::Payments));
+    client.pause(&PauseScope::Payments);
+    assert!(client.is_paused(&PauseScope::Payments));
+    assert!(!client.is_paused(&PauseScope::Deposits));
+
+    set_ledger_time(&env, due_date, 200);
+    assert_eq!(
+        client.try_pay_bill(&bill_id),
+        Err(Ok(Error::ContractPaused))
+    );
+
+    // Only the unallocated remainder comes back; the bill stays funded
+    let fee = amount * 200 / 10000;
+    let withdrawn = client.emergency_withdraw_own_cycle(&cycle_id);
+    assert_eq!(withdrawn, amount - fee - bill_amount);
+    assert_eq!(token.balance(&user), withdrawn);
+    assert_eq!(
+        client.try_emergency_withdraw_own_cycle(&cycle_id),
+        Err(Ok(Error::InsufficientFunds))
+    );
+
+    client.approve_admin_action(&admin, &AdminAction::Unpause(PauseScope::Payments));
+    client.unpause(&PauseScope::Payments);
     client.pay_bill(&bill_id);
+    assert_eq!(token.balance(&payee), bill_amount);
 }
 
 #[test]
-fn test_end_cycle() {
+fn test_withdraw_early() {
     let env = Env::default();
     env.mock_all_auths();
     set_ledger_time(&env, 1000, 100);
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
-
-    set_ledger_time(&env, 1000 + (91 * 24 * 60 * 60), 100 + (17280 * 91));
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
-    client.end_cycle(&cycle_id);
+    let bill_amount = 30_000_000_000_000_000_000i128;
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Rent"),
+        bill_amount,
+        1000 + (10 * 24 * 60 * 60),
+        false,
+        Vec::new(&env),
+        BillCategory::Housing,
+        payee.clone(),
+    ));
+    client.add_bills(&cycle_id, &bills);
 
-    let cycle = client.get_cycle(&cycle_id);
-    assert_eq!(cycle.is_active, false);
+    let fee = amount * 200 / 10000;
+    let unallocated = amount - fee - bill_amount;
+
+    // Funds reserved for the bill can't be withdrawn
+    assert_eq!(
+        client.try_withdraw_early(&cycle_id, &(unallocated + 1)),
+        Err(Ok(Error::EarlyWithdrawalNotAllowed))
+    );
+
+    // Default 10% penalty goes to the fee recipient (the admin)
+    let withdrawal = 10_000_000_000_000_000_000i128;
+    let received = client.withdraw_early(&cycle_id, &withdrawal);
+    assert_eq!(received, withdrawal * 9 / 10);
+    assert_eq!(token.balance(&user), received);
+    assert_eq!(token.balance(&admin), fee + withdrawal / 10);
+    assert_eq!(
+        client.get_cycle(&cycle_id).total_deposited,
+        amount - withdrawal
+    );
+
+    // No penalty once a change to zero has passed the timelock
+    apply_config_change(
+        &env,
+        &client,
+        &admin,
+        &ConfigChange::EarlyWithdrawalPenalty(0),
+    );
+    assert_eq!(client.early_withdrawal_penalty(), 0);
+    let rest = unallocated - withdrawal;
+    assert_eq!(client.withdraw_early(&cycle_id, &rest), rest);
+
+    let change = ConfigChange::EarlyWithdrawalPenalty(5001);
+    client.approve_admin_action(&admin, &AdminAction::ProposeConfigChange(change.clone()));
+    assert_eq!(
+        client.try_propose_config_change(&admin, &change),
+        Err(Ok(Error::InvalidPenalty))
+    );
+
+    // Ending the cycle still returns the reserved bill amount
+    client.approve_admin_action(&admin, &AdminAction::EndCycle(cycle_id));
+    client.admin_end_cycle(&cycle_id);
+    assert_eq!(token.balance(&user), received + rest + bill_amount);
 }
 
 #[test]
-#[should_panic(expected = "#30")]
-fn test_end_cycle_too_early() {
+fn test_cycle_keeps_token_after_set_usdc_token() {
     let env = Env::default();
     env.mock_all_auths();
     set_ledger_time(&env, 1000, 100);
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
+    let (new_token_address, new_token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
+    mint_tokens(&env, &new_token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &1, &amount);
+    assert_eq!(client.get_cycle(&cycle_id).token, usdc_token);
 
-    client.end_cycle(&cycle_id);
+    let bill_amount = 30_000_000_000_000_000_000i128;
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Rent"),
+        bill_amount,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Housing,
+        payee.clone(),
+    ));
+    let bill_id = client.add_bills(&cycle_id, &bills).get(0).unwrap();
+
+    // The default token has to be allowed first
+    let change = ConfigChange::UsdcToken(new_token_address.clone());
+    client.approve_admin_action(&admin, &AdminAction::ProposeConfigChange(change.clone()));
+    assert_eq!(
+        client.try_propose_config_change(&admin, &change),
+        Err(Ok(Error::TokenNotAllowed))
+    );
+    client.revoke_admin_approval(&admin, &AdminAction::ProposeConfigChange(change.clone()));
+
+    client.approve_admin_action(
+        &admin,
+        &AdminAction::AllowToken(new_token_address.clone(), 200),
+    );
+    client.allow_token(&new_token_address, &200);
+    apply_config_change(&env, &client, &admin, &change);
+    assert_eq!(client.usdc_token(), new_token_address);
+    let new_cycle_id = client.create_cycle(&user, &new_token_address, &1, &amount);
+    assert_eq!(client.get_cycle(&new_cycle_id).token, new_token_address);
+
+    // The first cycle still pays out in the token it was funded with
+    set_ledger_time(&env, due_date, 100 + (17280 * 10));
+    client.pay_bill(&bill_id);
+    assert_eq!(token.balance(&payee), bill_amount);
+    assert_eq!(new_token.balance(&payee), 0);
+
+    client.approve_admin_action(&admin, &AdminAction::EndCycle(cycle_id));
+    client.admin_end_cycle(&cycle_id);
+    let fee = amount * 200 / 10000;
+    assert_eq!(token.balance(&user), amount - fee - bill_amount);
+    assert_eq!(new_token.balance(&user), 0);
 }
 
 #[test]
@@ -340,13 +1804,14 @@ fn test_skip_bill() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     let due_date = 1000 + (10 * 24 * 60 * 60);
     let mut bills = Vec::new(&env);
@@ -357,6 +1822,7 @@ fn test_skip_bill() {
         false,
         Vec::new(&env),
         BillCategory::Other,
+        payee.clone(),
     ));
     let bill_ids = client.add_bills(&cycle_id, &bills);
     let bill_id = bill_ids.get(0).unwrap();
@@ -374,13 +1840,14 @@ fn test_get_cycle_bills() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     let due_date_1 = 1000 + (10 * 24 * 60 * 60); // 10 days
     let due_date_2 = 1000 + (15 * 24 * 60 * 60); // 15 days
@@ -393,6 +1860,7 @@ fn test_get_cycle_bills() {
         false,
         Vec::new(&env),
         BillCategory::Other,
+        payee.clone(),
     ));
     client.add_bills(&cycle_id, &bills);
 
@@ -404,6 +1872,7 @@ fn test_get_cycle_bills() {
         false,
         Vec::new(&env),
         BillCategory::Other,
+        payee.clone(),
     ));
     client.add_bills(&cycle_id, &bills);
 
@@ -423,13 +1892,14 @@ fn test_add_bill_day_29_validation() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     // Try to add bill with day 29 (should fail)
     let due_date = 1000 + (29 * 24 * 60 * 60);
@@ -441,6 +1911,7 @@ fn test_add_bill_day_29_validation() {
         true,
         Vec::new(&env),
         BillCategory::Other,
+        payee.clone(),
     ));
     client.add_bills(&cycle_id, &bills);
 }
@@ -454,13 +1925,14 @@ fn test_over_allocation_protection() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128; // 100 USDC
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     let due_date = 1000 + (10 * 24 * 60 * 60);
     let mut bills = Vec::new(&env);
@@ -471,6 +1943,7 @@ fn test_over_allocation_protection() {
         false,
         Vec::new(&env),
         BillCategory::Other,
+        payee.clone(),
     ));
     client.add_bills(&cycle_id, &bills);
 }
@@ -484,13 +1957,14 @@ fn test_double_payment_prevention_recurring() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     let due_date = 1000 + (10 * 24 * 60 * 60);
     let recurrence_calendar = Vec::from_array(&env, [1, 2, 3]);
@@ -503,15 +1977,137 @@ fn test_double_payment_prevention_recurring() {
         true,
         recurrence_calendar.clone(),
         BillCategory::Other,
+        payee.clone(),
     ));
     let bill_ids = client.add_bills(&cycle_id, &bills);
     let bill_id = bill_ids.get(0).unwrap();
 
     set_ledger_time(&env, due_date + 1, 100 + (17280 * 11));
-    client.admin_pay_bill(&bill_id);
+    client.approve_admin_action(&admin, &AdminAction::PayBill(bill_id));
+    client.admin_pay_bill(&admin, &bill_id);
 
     set_ledger_time(&env, due_date + (15 * 24 * 60 * 60), 100 + (17280 * 26));
-    client.admin_pay_bill(&bill_id);
+    client.approve_admin_action(&admin, &AdminAction::PayBill(bill_id));
+    client.admin_pay_bill(&admin, &bill_id);
+}
+
+#[test]
+fn test_end_cycle_after_recurring_payments() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let other_user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+    mint_tokens(&env, &token, &other_user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+    // A second user's deposit sits in the same vault
+    client.create_cycle(&other_user, &usdc_token, &3, &amount);
+
+    let bill_amount = 10_000_000_000_000_000_000i128;
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Rent"),
+        bill_amount,
+        due_date,
+        true,
+        Vec::from_array(&env, [1, 2, 3]),
+        BillCategory::Housing,
+        payee.clone(),
+    ));
+    let bill_ids = client.add_bills(&cycle_id, &bills);
+    let bill_id = bill_ids.get(0).unwrap();
+
+    set_ledger_time(&env, due_date + 1, 100 + (17280 * 11));
+    client.approve_admin_action(&admin, &AdminAction::PayBill(bill_id));
+    client.admin_pay_bill(&admin, &bill_id);
+
+    let next_due = client.get_bill(&bill_id).due_date;
+    set_ledger_time(&env, next_due + 1, 100 + (17280 * 42));
+    client.approve_admin_action(&admin, &AdminAction::PayBill(bill_id));
+    client.admin_pay_bill(&admin, &bill_id);
+
+    // Recurring bill is still running, so it isn't flagged as paid
+    let bill = client.get_bill(&bill_id);
+    assert_eq!(bill.is_paid, false);
+
+    let cycle = client.get_cycle(&cycle_id);
+    assert_eq!(cycle.total_disbursed, bill_amount * 2);
+
+    client.approve_admin_action(&admin, &AdminAction::EndCycle(cycle_id));
+    client.admin_end_cycle(&cycle_id);
+
+    let fee = amount * 200 / 10000;
+    assert_eq!(token.balance(&payee), bill_amount * 2);
+    assert_eq!(token.balance(&user), amount - fee - bill_amount * 2);
+
+    // The other user's deposit is untouched
+    assert_eq!(token.balance(&client.address), amount - fee);
+}
+
+#[test]
+fn test_recurring_due_date_keeps_day_of_month() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let start = 1735689600; // 2025-01-01 00:00:00 UTC
+    set_ledger_time(&env, start, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    // Three calendar months, not 90 days
+    let cycle = client.get_cycle(&cycle_id);
+    assert_eq!(cycle.end_date, 1743465600); // 2025-04-01
+
+    let jan_15 = 1736899200; // 2025-01-15
+    let feb_15 = 1739577600; // 2025-02-15
+    let mar_15 = 1741996800; // 2025-03-15
+
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Rent"),
+        10_000_000_000_000_000_000i128,
+        jan_15,
+        true,
+        Vec::new(&env),
+        BillCategory::Housing,
+        payee.clone(),
+    ));
+    let bill_ids = client.add_bills(&cycle_id, &bills);
+    let bill_id = bill_ids.get(0).unwrap();
+
+    set_ledger_time(&env, jan_15 + 1, 100 + (17280 * 14));
+    client.pay_bill(&bill_id);
+    assert_eq!(client.get_bill(&bill_id).due_date, feb_15);
+
+    // February is only 28 days long but the bill stays on the 15th
+    set_ledger_time(&env, feb_15 + 1, 100 + (17280 * 45));
+    client.pay_bill(&bill_id);
+    assert_eq!(client.get_bill(&bill_id).due_date, mar_15);
+
+    set_ledger_time(&env, mar_15 + 1, 100 + (17280 * 73));
+    client.pay_bill(&bill_id);
+
+    let bill = client.get_bill(&bill_id);
+    assert_eq!(bill.is_paid, true);
+    assert_eq!(token.balance(&payee), 30_000_000_000_000_000_000i128);
 }
 
 #[test]
@@ -522,13 +2118,14 @@ fn test_skip_bill_recurring() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     let due_date = 1000 + (10 * 24 * 60 * 60);
     let recurrence_calendar = Vec::from_array(&env, [1, 2, 3]);
@@ -541,6 +2138,7 @@ fn test_skip_bill_recurring() {
         true,
         recurrence_calendar.clone(),
         BillCategory::Other,
+        payee.clone(),
     ));
     let bill_ids = client.add_bills(&cycle_id, &bills);
     let bill_id = bill_ids.get(0).unwrap();
@@ -553,6 +2151,12 @@ fn test_skip_bill_recurring() {
     assert_eq!(bill.is_recurring, true);
 
     assert!(bill.last_paid_date.is_some());
+
+    // The skipped occurrence moves the due date on by one calendar month
+    assert_eq!(
+        bill.due_date,
+        1000 + (10 * 24 * 60 * 60) + (31 * 24 * 60 * 60)
+    );
 }
 
 #[test]
@@ -563,13 +2167,14 @@ fn test_delete_bill() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     let due_date = 1000 + (10 * 24 * 60 * 60);
     let recurrence_calendar = Vec::from_array(&env, [1, 2, 3]);
@@ -582,6 +2187,7 @@ fn test_delete_bill() {
         true,
         recurrence_calendar.clone(),
         BillCategory::Other,
+        payee.clone(),
     ));
     let bill_ids = client.add_bills(&cycle_id, &bills);
     let bill_id = bill_ids.get(0).unwrap();
@@ -592,6 +2198,165 @@ fn test_delete_bill() {
 
     let bills = client.get_cycle_bills(&cycle_id);
     assert_eq!(bills.len(), 0);
+    assert_eq!(client.get_due_bills(&due_date, &due_date, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_add_emergency_bill() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    // Due in 2 days on the 29th of the month, both rejected for regular bills
+    set_ledger_time(&env, 1000 + (26 * 24 * 60 * 60), 100 + (17280 * 26));
+    let due_date = 1000 + (28 * 24 * 60 * 60);
+    let bill_id = client.add_emergency_bill(
+        &cycle_id,
+        &String::from_str(&env, "Hospital"),
+        &20_000_000_000_000_000_000i128,
+        &due_date,
+        &BillCategory::Healthcare,
+        &payee,
+    );
+
+    let bill = client.get_bill(&bill_id);
+    assert_eq!(bill.is_emergency, true);
+
+    set_ledger_time(&env, due_date + 1, 100 + (17280 * 28));
+    client.pay_bill(&bill_id);
+    assert_eq!(token.balance(&payee), 20_000_000_000_000_000_000i128);
+
+    // 24.5 USDC cap (25% of 98), 20 already used
+    assert_eq!(
+        client.try_add_emergency_bill(
+            &cycle_id,
+            &String::from_str(&env, "Pharmacy"),
+            &5_000_000_000_000_000_000i128,
+            &due_date,
+            &BillCategory::Healthcare,
+            &payee,
+        ),
+        Err(Ok(Error::EmergencyBillLimitExceeded))
+    );
+
+    client.add_emergency_bill(
+        &cycle_id,
+        &String::from_str(&env, "Pharmacy"),
+        &1_000_000_000_000_000_000i128,
+        &due_date,
+        &BillCategory::Healthcare,
+        &payee,
+    );
+
+    // Only two emergency bills per cycle
+    assert_eq!(
+        client.try_add_emergency_bill(
+            &cycle_id,
+            &String::from_str(&env, "Dentist"),
+            &1_000_000_000_000_000_000i128,
+            &due_date,
+            &BillCategory::Healthcare,
+            &payee,
+        ),
+        Err(Ok(Error::EmergencyBillLimitExceeded))
+    );
+}
+
+#[test]
+fn test_update_bill() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
+
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Rent"),
+        60_000_000_000_000_000_000i128,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Housing,
+        payee.clone(),
+    ));
+    let bill_ids = client.add_bills(&cycle_id, &bills);
+    let bill_id = bill_ids.get(0).unwrap();
+
+    set_ledger_time(&env, 1000 + (31 * 24 * 60 * 60), 100 + (17280 * 31));
+
+    // Deleting a bill uses up this month's adjustment
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Gym"),
+        10_000_000_000_000_000_000i128,
+        1000 + (45 * 24 * 60 * 60),
+        false,
+        Vec::new(&env),
+        BillCategory::Entertainment,
+        payee.clone(),
+    ));
+    let gym_id = client.add_bills(&cycle_id, &bills).get(0).unwrap();
+    client.delete_bill(&gym_id);
+
+    // Updating still works, and the bill's own 60 is not counted twice against the 98 available
+    let new_due_date = 1000 + (50 * 24 * 60 * 60);
+    client.update_bill(
+        &bill_id,
+        &String::from_str(&env, "Rent 2.0"),
+        &88_000_000_000_000_000_000i128,
+        &new_due_date,
+        &BillCategory::Housing,
+    );
+
+    let bill = client.get_bill(&bill_id);
+    assert_eq!(bill.id, bill_id);
+    assert_eq!(bill.name, String::from_str(&env, "Rent 2.0"));
+    assert_eq!(bill.amount, 88_000_000_000_000_000_000i128);
+    assert_eq!(bill.due_date, new_due_date);
+
+    assert_eq!(
+        client.try_update_bill(
+            &bill_id,
+            &String::from_str(&env, "Rent 2.0"),
+            &99_000_000_000_000_000_000i128,
+            &new_due_date,
+            &BillCategory::Housing,
+        ),
+        Err(Ok(Error::InsufficientFunds))
+    );
+
+    assert_eq!(
+        client.try_update_bill(
+            &bill_id,
+            &String::from_str(&env, "Rent 2.0"),
+            &88_000_000_000_000_000_000i128,
+            &(1000 + (33 * 24 * 60 * 60)),
+            &BillCategory::Housing,
+        ),
+        Err(Ok(Error::BillLeadTimeTooShort))
+    );
 }
 
 #[test]
@@ -602,13 +2367,14 @@ fn test_batch_add_bills() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     let mut bills = Vec::new(&env);
 
@@ -623,6 +2389,7 @@ fn test_batch_add_bills() {
         false,
         Vec::new(&env),
         BillCategory::Other,
+        payee.clone(),
     ));
 
     bills.push_back((
@@ -632,6 +2399,7 @@ fn test_batch_add_bills() {
         false,
         Vec::new(&env),
         BillCategory::Other,
+        payee.clone(),
     ));
 
     bills.push_back((
@@ -641,6 +2409,7 @@ fn test_batch_add_bills() {
         false,
         Vec::new(&env),
         BillCategory::Other,
+        payee.clone(),
     ));
 
     let bill_ids = client.add_bills(&cycle_id, &bills);
@@ -669,7 +2438,7 @@ fn test_end_cycle_anyone_after_end_date() {
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     set_ledger_time(&env, 1000 + (100 * 24 * 60 * 60), 100 + (17280 * 100));
 
@@ -695,7 +2464,7 @@ fn test_end_cycle_too_early_anyone() {
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     set_ledger_time(&env, 1000 + (10 * 24 * 60 * 60), 100 + (17280 * 10));
     // Should fail - cycle hasn't reached end_date yet
@@ -703,36 +2472,91 @@ fn test_end_cycle_too_early_anyone() {
 }
 
 #[test]
-fn test_admin_transfer_and_cancel() {
-    let env = Env::default();
-    env.mock_all_auths();
-
-    let admin = Address::generate(&env);
-    let new_admin = Address::generate(&env);
-    let (usdc_token, _) = create_token_contract(&env, &admin);
-    let client = create_lockedin_contract(&env, &admin, &usdc_token);
-
-    client.transfer_admin(&new_admin, &1000);
-
-    client.cancel_admin_transfer();
-}
-
-#[test]
-#[should_panic(expected = "#42")]
-fn test_admin_transfer_race_condition_protection() {
+fn test_multisig_admin() {
     let env = Env::default();
     env.mock_all_auths();
     set_ledger_time(&env, 1000, 100);
 
     let admin = Address::generate(&env);
-    let new_admin_1 = Address::generate(&env);
-    let new_admin_2 = Address::generate(&env);
+    let signer_2 = Address::generate(&env);
+    let signer_3 = Address::generate(&env);
+    let outsider = Address::generate(&env);
     let (usdc_token, _) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
-    client.transfer_admin(&new_admin_1, &2000);
-
-    client.transfer_admin(&new_admin_2, &3000);
+    // Nothing runs without an approval, even for the only signer
+    assert_eq!(
+        client.try_pause(&PauseScope::Deposits),
+        Err(Ok(Error::InsufficientApprovals))
+    );
+
+    client.approve_admin_action(&admin, &AdminAction::AddSigner(signer_2.clone()));
+    client.add_signer(&signer_2);
+    client.approve_admin_action(&admin, &AdminAction::AddSigner(signer_3.clone()));
+    client.add_signer(&signer_3);
+    client.approve_admin_action(&admin, &AdminAction::SetThreshold(2));
+    client.set_threshold(&2);
+    assert_eq!(client.admin_threshold(), 2);
+    assert_eq!(client.admin_signers().len(), 3);
+
+    // 2-of-3: one approval isn't enough, and outsiders can't approve
+    let pause = AdminAction::Pause(PauseScope::Deposits);
+    client.approve_admin_action(&admin, &pause);
+    assert_eq!(
+        client.try_approve_admin_action(&admin, &pause),
+        Err(Ok(Error::AlreadyApproved))
+    );
+    assert_eq!(
+        client.try_approve_admin_action(&outsider, &pause),
+        Err(Ok(Error::NotSigner))
+    );
+    assert_eq!(
+        client.try_pause(&PauseScope::Deposits),
+        Err(Ok(Error::InsufficientApprovals))
+    );
+
+    client.approve_admin_action(&signer_2, &pause);
+    client.pause(&PauseScope::Deposits);
+    assert!(client.is_paused(&PauseScope::Deposits));
+
+    // Approvals are used up by the action
+    assert_eq!(client.get_admin_approvals(&pause).len(), 0);
+    client.approve_admin_action(&admin, &pause);
+    client.revoke_admin_approval(&admin, &pause);
+    assert_eq!(
+        client.try_revoke_admin_approval(&admin, &pause),
+        Err(Ok(Error::ApprovalNotFound))
+    );
+
+    // A removed signer's earlier approval no longer counts
+    let unpause = AdminAction::Unpause(PauseScope::Deposits);
+    client.approve_admin_action(&signer_3, &unpause);
+    let remove = AdminAction::RemoveSigner(signer_3.clone());
+    client.approve_admin_action(&admin, &remove);
+    client.approve_admin_action(&signer_2, &remove);
+    client.remove_signer(&signer_3);
+    client.approve_admin_action(&admin, &unpause);
+    assert_eq!(
+        client.try_unpause(&PauseScope::Deposits),
+        Err(Ok(Error::InsufficientApprovals))
+    );
+
+    // Two signers can't go below the threshold of 2
+    let remove = AdminAction::RemoveSigner(signer_2.clone());
+    client.approve_admin_action(&admin, &remove);
+    client.approve_admin_action(&signer_2, &remove);
+    assert_eq!(
+        client.try_remove_signer(&signer_2),
+        Err(Ok(Error::InvalidThreshold))
+    );
+
+    let threshold = AdminAction::SetThreshold(3);
+    client.approve_admin_action(&admin, &threshold);
+    client.approve_admin_action(&signer_2, &threshold);
+    assert_eq!(
+        client.try_set_threshold(&3),
+        Err(Ok(Error::InvalidThreshold))
+    );
 }
 
 #[test]
@@ -743,13 +2567,14 @@ fn test_batch_skip_bills() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &3, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &3, &amount);
 
     let due_date_1 = 1000 + (10 * 24 * 60 * 60);
     let due_date_2 = 1000 + (15 * 24 * 60 * 60);
@@ -762,6 +2587,7 @@ fn test_batch_skip_bills() {
         false,
         Vec::new(&env),
         BillCategory::Other,
+        payee.clone(),
     ));
     let bill_ids = client.add_bills(&cycle_id, &bills);
     let bill_id_1 = bill_ids.get(0).unwrap();
@@ -774,6 +2600,7 @@ fn test_batch_skip_bills() {
         true,
         Vec::from_array(&env, [1, 2]),
         BillCategory::Other,
+        payee.clone(),
     ));
     let bill_ids = client.add_bills(&cycle_id, &bills);
     let bill_id_2 = bill_ids.get(0).unwrap();
@@ -799,13 +2626,14 @@ fn test_recurring_bill_with_recurrence_calendar() {
 
     let admin = Address::generate(&env);
     let user = Address::generate(&env);
+    let payee = Address::generate(&env);
     let (usdc_token, token) = create_token_contract(&env, &admin);
     let client = create_lockedin_contract(&env, &admin, &usdc_token);
 
     let amount = 100_000_000_000_000_000_000i128;
     mint_tokens(&env, &token, &user, amount);
 
-    let cycle_id = client.create_cycle(&user, &6, &amount);
+    let cycle_id = client.create_cycle(&user, &usdc_token, &6, &amount);
 
     let due_date = 1000 + (10 * 24 * 60 * 60);
     let recurrence_calendar = Vec::from_array(&env, [1, 3, 5]);
@@ -818,6 +2646,7 @@ fn test_recurring_bill_with_recurrence_calendar() {
         true,
         recurrence_calendar.clone(),
         BillCategory::Other,
+        payee.clone(),
     ));
     let bill_ids = client.add_bills(&cycle_id, &bills);
     let bill_id = bill_ids.get(0).unwrap();
@@ -825,4 +2654,380 @@ fn test_recurring_bill_with_recurrence_calendar() {
     let bill = client.get_bill(&bill_id);
     assert_eq!(bill.is_recurring, true);
     assert_eq!(bill.recurrence_calendar.len(), 3);
+
+    // January occurrence
+    set_ledger_time(&env, due_date + 1, 100 + (17280 * 11));
+    client.approve_admin_action(&admin, &AdminAction::PayBill(bill_id));
+    client.admin_pay_bill(&admin, &bill_id);
+
+    // February is not in the calendar, so the next due date lands in March
+    let bill = client.get_bill(&bill_id);
+    let march_due = bill.due_date;
+    assert!(march_due > 1000 + (59 * 24 * 60 * 60));
+    assert!(march_due < 1000 + (90 * 24 * 60 * 60));
+
+    set_ledger_time(&env, due_date + (31 * 24 * 60 * 60), 100 + (17280 * 41));
+    client.approve_admin_action(&admin, &AdminAction::PayBill(bill_id));
+    assert_eq!(
+        client.try_admin_pay_bill(&admin, &bill_id),
+        Err(Ok(Error::BillNotDueYet))
+    );
+
+    // March occurrence
+    set_ledger_time(&env, march_due + 1, 100 + (17280 * 71));
+    client.pay_bill(&bill_id);
+
+    // April is skipped as well
+    let bill = client.get_bill(&bill_id);
+    assert!(bill.due_date > 1000 + (120 * 24 * 60 * 60));
+
+    set_ledger_time(&env, 1000 + (181 * 24 * 60 * 60), 100 + (17280 * 181));
+    client.end_cycle(&cycle_id);
+
+    // Only January and March were ever paid
+    assert_eq!(token.balance(&payee), 20_000_000_000_000_000_000i128);
+}
+
+#[test]
+fn test_recurrence_calendar_allocation() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount);
+
+    let cycle_id = client.create_cycle(&user, &usdc_token, &6, &amount);
+
+    // Three months reserved: 90 of the 98 available
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Quarterly Bill"),
+        30_000_000_000_000_000_000i128,
+        due_date,
+        true,
+        Vec::from_array(&env, [1, 3, 5]),
+        BillCategory::Other,
+        payee.clone(),
+    ));
+    client.add_bills(&cycle_id, &bills);
+
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Too Much"),
+        9_000_000_000_000_000_000i128,
+        due_date,
+        false,
+        Vec::new(&env),
+        BillCategory::Other,
+        payee.clone(),
+    ));
+    assert_eq!(
+        client.try_add_bills(&cycle_id, &bills),
+        Err(Ok(Error::InsufficientFunds))
+    );
+
+    // Two bills that each fit in the remaining 8 can't both be added in one batch
+    let mut bills = Vec::new(&env);
+    for name in ["Laptop", "Phone"] {
+        bills.push_back((
+            String::from_str(&env, name),
+            5_000_000_000_000_000_000i128,
+            due_date,
+            false,
+            Vec::new(&env),
+            BillCategory::Other,
+            payee.clone(),
+        ));
+    }
+    assert_eq!(
+        client.try_add_bills(&cycle_id, &bills),
+        Err(Ok(Error::InsufficientFunds))
+    );
+}
+
+#[test]
+fn test_migrate_from_old_storage() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    assert_eq!(client.version(), 9);
+    client.approve_admin_action(&admin, &AdminAction::Migrate(10));
+    assert_eq!(client.try_migrate(&10), Err(Ok(Error::AlreadyMigrated)));
+
+    // Rebuild the storage a version 2 deployment would have: an active cycle with a paid
+    // and an unpaid bill, an ended cycle and another active cycle with a bill
+    let amount = 100_000_000_000_000_000_000i128;
+    let fee = amount * 200 / 10000;
+    let paid_amount = 10_000_000_000_000_000_000i128;
+    let unpaid_amount = 20_000_000_000_000_000_000i128;
+    let due_date = 1000 + (10 * 24 * 60 * 60);
+    mint_tokens(&env, &token, &client.address, amount - fee - paid_amount);
+
+    env.as_contract(&client.address, || {
+        let cycle = BillCycleV2 {
+            user: user.clone(),
+            start_date: 1000,
+            end_date: 1000 + (90 * 24 * 60 * 60),
+            total_deposited: amount,
+            operating_fee: fee,
+            fee_percentage: 200,
+            is_active: true,
+            last_adjustment_month: 0,
+            total_disbursed: paid_amount,
+            emergency_bill_count: 0,
+            emergency_allocated: 0,
+            keeper_budget: 0,
+        };
+        let ended_cycle = BillCycleV2 {
+            is_active: false,
+            ..cycle.clone()
+        };
+        let other_cycle = BillCycleV2 {
+            total_disbursed: 0,
+            emergency_bill_count: 1,
+            ..cycle.clone()
+        };
+        let other_bill = BillV2 {
+            id: 3,
+            cycle_id: 3,
+            name: String::from_str(&env, "Phone"),
+            amount: paid_amount,
+            due_date,
+            is_paid: false,
+            is_recurring: false,
+            recurrence_calendar: Vec::new(&env),
+            last_paid_date: None,
+            category: BillCategory::Utilities,
+            payee: user.clone(),
+            is_emergency: false,
+        };
+        let paid_bill = BillV2 {
+            id: 1,
+            cycle_id: 1,
+            name: String::from_str(&env, "Gym"),
+            amount: paid_amount,
+            due_date: 1000 + (8 * 24 * 60 * 60),
+            is_paid: true,
+            is_recurring: false,
+            recurrence_calendar: Vec::new(&env),
+            last_paid_date: Some(1000),
+            category: BillCategory::Healthcare,
+            payee: user.clone(),
+            is_emergency: false,
+        };
+        let unpaid_bill = BillV2 {
+            id: 2,
+            name: String::from_str(&env, "Rent"),
+            amount: unpaid_amount,
+            due_date,
+            is_paid: false,
+            last_paid_date: None,
+            category: BillCategory::Housing,
+            ..paid_bill.clone()
+        };
+
+        let instance = env.storage().instance();
+        instance.set(&DataKey::Version, &2u32);
+        instance.remove(&DataKey::AdminSigners);
+        instance.remove(&DataKey::AdminThreshold);
+        instance.set(&DataKey::Admin, &admin);
+        instance.remove(&DataKey::AllowedTokens);
+        instance.remove(&DataKey::TokenFeePercentage(usdc_token.clone()));
+        instance.set(&DataKey::FeePercentage, &300u32);
+        instance.set(&DataKey::CycleCounter, &3u64);
+        instance.set(&DataKey::BillCounter, &3u64);
+
+        let persistent = env.storage().persistent();
+        persistent.set(&DataKey::Cycle(1), &cycle);
+        persistent.set(&DataKey::Cycle(2), &ended_cycle);
+        persistent.set(&DataKey::Cycle(3), &other_cycle);
+        persistent.set(&DataKey::Bill(1), &paid_bill);
+        persistent.set(&DataKey::Bill(2), &unpaid_bill);
+        persistent.set(&DataKey::Bill(3), &other_bill);
+        persistent.set(&DataKey::CycleBills(1), &Vec::from_array(&env, [1u64, 2]));
+        persistent.set(&DataKey::CycleBills(3), &Vec::from_array(&env, [3u64]));
+        persistent.set(
+            &DataKey::DueBills(due_date / 86400),
+            &Vec::from_array(&env, [2u64]),
+        );
+        let cycle_ids = Vec::from_array(&env, [1u64, 2, 3]);
+        persistent.set(&DataKey::UserCycles(user.clone()), &cycle_ids);
+        persistent.set(&DataKey::AllCycles, &cycle_ids);
+    });
+
+    // The code under test stands in for the new release; uploading no bytes gives the
+    // hash the test environment runs it under
+    let new_wasm_hash = env.deployer().upload_contract_wasm(Bytes::new(&env));
+    client.approve_admin_action(&admin, &AdminAction::Upgrade(new_wasm_hash.clone()));
+    client.upgrade(&new_wasm_hash);
+    assert_eq!(client.version(), 2);
+    assert_eq!(
+        client.try_deposit_to_cycle(&1, &amount),
+        Err(Ok(Error::MigrationInProgress))
+    );
+
+    // One cycle per call, writes stay blocked until the last one
+    client.approve_admin_action(&admin, &AdminAction::Migrate(1));
+    assert_eq!(client.migrate(&1), false);
+    assert_eq!(client.version(), 2);
+    assert_eq!(
+        client.try_emergency_withdraw_own_cycle(&2),
+        Err(Ok(Error::MigrationInProgress))
+    );
+    client.approve_admin_action(&admin, &AdminAction::Migrate(3));
+    assert_eq!(client.migrate(&3), true);
+    assert_eq!(client.version(), 9);
+    assert_eq!(
+        client.allowed_tokens(),
+        Vec::from_array(&env, [usdc_token.clone()])
+    );
+    assert_eq!(client.fee_percentage(&usdc_token), 300);
+    assert_eq!(
+        client.admin_signers(),
+        Vec::from_array(&env, [admin.clone()])
+    );
+    client.approve_admin_action(&admin, &AdminAction::Migrate(1));
+    assert_eq!(client.try_migrate(&1), Err(Ok(Error::AlreadyMigrated)));
+
+    let cycle = client.get_cycle(&1);
+    assert_eq!(cycle.total_disbursed, paid_amount);
+    assert_eq!(cycle.keeper_budget, 0);
+    assert_eq!(cycle.token, usdc_token);
+    assert_eq!(client.get_cycle(&2).is_active, false);
+    assert_eq!(client.get_cycle(&2).auto_renew, false);
+    assert_eq!(client.get_cycle(&2).next_cycle_id, None);
+    assert_eq!(client.get_cycle(&3).emergency_bill_count, 1);
+    assert_eq!(client.get_cycle(&3).token, usdc_token);
+
+    // Liabilities are rebuilt from the active cycles
+    let report = client.check_solvency().get(0).unwrap();
+    assert_eq!(report.liabilities, (amount - fee) * 2 - paid_amount);
+
+    // Bills keep their payee and unpaid ones stay in the due-date index
+    let bill = client.get_bill(&2);
+    assert_eq!(bill.payee, user);
+    assert_eq!(bill.is_emergency, false);
+    assert_eq!(bill.installment_count, 0);
+    assert_eq!(client.get_bill(&3).remaining_balance, 0);
+    assert_eq!(client.get_bill(&3).is_variable, false);
+    assert_eq!(
+        client.get_due_bills(&due_date, &due_date, &0, &10),
+        Vec::from_array(&env, [2u64])
+    );
+
+    set_ledger_time(&env, due_date, 200);
+    client.pay_bill(&2);
+    assert_eq!(token.balance(&user), unpaid_amount);
+
+    client.approve_admin_action(&admin, &AdminAction::EndCycle(1));
+    client.admin_end_cycle(&1);
+    assert_eq!(token.balance(&user), amount - fee - paid_amount);
+}
+
+#[test]
+fn test_migrate_keeps_tracked_liabilities() {
+    let env = Env::default();
+    env.mock_all_auths();
+    set_ledger_time(&env, 1000, 100);
+
+    let admin = Address::generate(&env);
+    let user = Address::generate(&env);
+    let payee = Address::generate(&env);
+    let (usdc_token, token) = create_token_contract(&env, &admin);
+    let client = create_lockedin_contract(&env, &admin, &usdc_token);
+
+    let amount = 100_000_000_000_000_000_000i128;
+    let fee = amount * 200 / 10000;
+    let bill_amount = 10_000_000_000_000_000_000i128;
+    mint_tokens(&env, &token, &user, amount * 2);
+
+    let first_cycle = client.create_cycle(&user, &usdc_token, &3, &amount);
+    let second_cycle = client.create_cycle(&user, &usdc_token, &3, &amount);
+    let mut bills = Vec::new(&env);
+    bills.push_back((
+        String::from_str(&env, "Rent"),
+        bill_amount,
+        1000 + (10 * 24 * 60 * 60),
+        false,
+        Vec::new(&env),
+        BillCategory::Housing,
+        payee.clone(),
+    ));
+    let first_bill = client.add_bills(&first_cycle, &bills).get(0).unwrap();
+    let second_bill = client.add_bills(&second_cycle, &bills).get(0).unwrap();
+    let liabilities = client.check_solvency().get(0).unwrap().liabilities;
+
+    // Put the bills back in the version 8 layout, from before variable amounts
+    env.as_contract(&client.address, || {
+        let persistent = env.storage().persistent();
+        for bill_id in [first_bill, second_bill] {
+            let bill: Bill = persistent.get(&DataKey::Bill(bill_id)).unwrap();
+            let old_bill = BillV3 {
+                id: bill.id,
+                cycle_id: bill.cycle_id,
+                name: bill.name,
+                amount: bill.amount,
+                due_date: bill.due_date,
+                is_paid: bill.is_paid,
+                is_recurring: bill.is_recurring,
+                recurrence_calendar: bill.recurrence_calendar,
+                last_paid_date: bill.last_paid_date,
+                category: bill.category,
+                payee: bill.payee,
+                is_emergency: bill.is_emergency,
+                installment_count: bill.installment_count,
+                installments_paid: bill.installments_paid,
+                remaining_balance: bill.remaining_balance,
+            };
+            persistent.set(&DataKey::Bill(bill_id), &old_bill);
+        }
+        env.storage().instance().set(&DataKey::Version, &8u32);
+    });
+
+    let new_wasm_hash = env.deployer().upload_contract_wasm(Bytes::new(&env));
+    client.approve_admin_action(&admin, &AdminAction::Upgrade(new_wasm_hash.clone()));
+    client.upgrade(&new_wasm_hash);
+    client.approve_admin_action(&admin, &AdminAction::Pause(PauseScope::Deposits));
+    client.pause(&PauseScope::Deposits);
+
+    // Version 8 already tracks liabilities, so a partial migration leaves the total alone
+    client.approve_admin_action(&admin, &AdminAction::Migrate(1));
+    assert_eq!(client.migrate(&1), false);
+    assert_eq!(
+        client.check_solvency().get(0).unwrap().liabilities,
+        liabilities
+    );
+
+    // The escape hatch works on the cycle already migrated, not on the other one
+    assert_eq!(
+        client.try_emergency_withdraw_own_cycle(&second_cycle),
+        Err(Ok(Error::MigrationInProgress))
+    );
+    let withdrawn = client.emergency_withdraw_own_cycle(&first_cycle);
+    assert_eq!(withdrawn, amount - fee - bill_amount);
+    assert_eq!(token.balance(&user), withdrawn);
+
+    client.approve_admin_action(&admin, &AdminAction::Migrate(1));
+    assert_eq!(client.migrate(&1), true);
+    assert_eq!(client.version(), 9);
+    assert_eq!(
+        client.check_solvency().get(0).unwrap().liabilities,
+        liabilities - withdrawn
+    );
+    assert_eq!(client.get_bill(&second_bill).is_variable, false);
+    assert_eq!(client.get_bill(&second_bill).amount, bill_amount);
 }
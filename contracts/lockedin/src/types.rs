@@ -1,4 +1,4 @@
-use soroban_sdk::{contracttype, Address, String, Vec};
+use soroban_sdk::{contracttype, Address, BytesN, String, Vec};
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -15,10 +15,77 @@ pub enum BillCategory {
     Other,
 }
 
+// One add_bills entry: (name, amount, due_date, is_recurring, recurrence_calendar, category, payee)
+pub type BillInput = (String, i128, u64, bool, Vec<u32>, BillCategory, Address);
+
+// Roles granted by the admin signers; a signer without the role needs the action approved
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Role {
+    Keeper,     // pays due bills
+    FeeManager, // sets per-token fees, the fee recipient and the early withdrawal penalty
+}
+
+// Groups of entry points the admin can pause independently
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PauseScope {
+    Deposits,    // creating and funding cycles, adding and editing bills
+    Payments,    // every bill payment path
+    CycleEnding, // end_cycle, admin_end_cycle and withdraw_early
+}
+
+// Operations that need threshold approvals from the admin signers
+// Each approval covers one exact action, arguments included
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AdminAction {
+    AddSigner(Address),
+    RemoveSigner(Address),
+    SetThreshold(u32),
+    GrantRole(Role, Address),
+    RevokeRole(Role, Address),
+    ProposeConfigChange(ConfigChange),
+    ExecuteConfigChange(u64), // proposal_id
+    CancelConfigChange(u64),  // proposal_id
+    AllowToken(Address, u32), // token, fee in basis points
+    DisallowToken(Address),
+    SetPaymentWindow(u32, u32), // days_before, days_after
+    SetKeeperTip(i128, u32),    // tip, fee_share_bps
+    Pause(PauseScope),
+    Unpause(PauseScope),
+    Upgrade(BytesN<32>),
+    Migrate(u32), // limit
+    EndCycle(u64),
+    PayBill(u64),
+    PayBills(Vec<u64>),
+    PayDueBills(u64, u32, u32), // day, cursor, limit
+}
+
+// Configuration changes that go through the propose/execute timelock
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConfigChange {
+    FeePercentage(Address, u32), // token, fee in basis points
+    FeeRecipient(Address),
+    UsdcToken(Address),
+    EarlyWithdrawalPenalty(u32), // basis points
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigProposal {
+    pub change: ConfigChange,
+    pub proposer: Address,
+    pub executable_from: u32, // ledger sequence
+    pub expires_at: u32,      // ledger sequence
+}
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct BillCycle {
     pub user: Address,
+    pub token: Address, // token the cycle was funded in; all its payouts use it
     pub start_date: u64,
     pub end_date: u64,
     pub total_deposited: i128,
@@ -26,6 +93,13 @@ pub struct BillCycle {
     pub fee_percentage: u32,
     pub is_active: bool,
     pub last_adjustment_month: u32,
+    pub total_disbursed: i128,
+    pub emergency_bill_count: u32,
+    pub emergency_allocated: i128,
+    pub keeper_budget: i128,
+    pub auto_renew: bool, // end_cycle renews instead of refunding the surplus
+    pub previous_cycle_id: Option<u64>, // cycle this one was renewed from
+    pub next_cycle_id: Option<u64>, // cycle this one was renewed into
 }
 
 #[contracttype]
@@ -41,23 +115,191 @@ pub struct Bill {
     pub recurrence_calendar: Vec<u32>,
     pub last_paid_date: Option<u64>,
     pub category: BillCategory,
+    pub payee: Address,
+    pub is_emergency: bool,
+    pub installment_count: u32, // 0 for bills without an installment plan
+    pub installments_paid: u32,
+    pub remaining_balance: i128, // installment bills only: part of the plan total not yet paid
+    pub is_variable: bool,       // amount is the cap, actual_amount what gets paid
+    pub actual_amount: Option<i128>, // variable bills only: set for the current occurrence
+}
+
+// Total and number of monthly payments for add_installment_bill
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstallmentPlan {
+    pub total_amount: i128,
+    pub installment_count: u32,
+}
+
+// Payee's request to be paid for one occurrence of a bill
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Invoice {
+    pub bill_id: u64,
+    pub amount: i128,
+    pub reference: String,
+    pub due_date: u64, // occurrence the invoice is for
+    pub submitted_at: u64,
+    pub is_approved: bool,
+}
+
+// Layouts written by earlier versions of the contract, read only by migrate
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BillCycleV2 {
+    pub user: Address,
+    pub start_date: u64,
+    pub end_date: u64,
+    pub total_deposited: i128,
+    pub operating_fee: i128,
+    pub fee_percentage: u32,
+    pub is_active: bool,
+    pub last_adjustment_month: u32,
+    pub total_disbursed: i128,
+    pub emergency_bill_count: u32,
+    pub emergency_allocated: i128,
+    pub keeper_budget: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BillCycleV3 {
+    pub user: Address,
+    pub token: Address,
+    pub start_date: u64,
+    pub end_date: u64,
+    pub total_deposited: i128,
+    pub operating_fee: i128,
+    pub fee_percentage: u32,
+    pub is_active: bool,
+    pub last_adjustment_month: u32,
+    pub total_disbursed: i128,
+    pub emergency_bill_count: u32,
+    pub emergency_allocated: i128,
+    pub keeper_budget: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BillV2 {
+    pub id: u64,
+    pub cycle_id: u64,
+    pub name: String,
+    pub amount: i128,
+    pub due_date: u64,
+    pub is_paid: bool,
+    pub is_recurring: bool,
+    pub recurrence_calendar: Vec<u32>,
+    pub last_paid_date: Option<u64>,
+    pub category: BillCategory,
+    pub payee: Address,
+    pub is_emergency: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BillV3 {
+    pub id: u64,
+    pub cycle_id: u64,
+    pub name: String,
+    pub amount: i128,
+    pub due_date: u64,
+    pub is_paid: bool,
+    pub is_recurring: bool,
+    pub recurrence_calendar: Vec<u32>,
+    pub last_paid_date: Option<u64>,
+    pub category: BillCategory,
+    pub payee: Address,
+    pub is_emergency: bool,
+    pub installment_count: u32,
+    pub installments_paid: u32,
+    pub remaining_balance: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentWindow {
+    pub days_before: u32,
+    pub days_after: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KeeperTip {
+    pub tip: i128,
+    pub fee_share_bps: u32,
+}
+
+// Read-only view returned by get_cycle_summary
+// reserved covers every unpaid occurrence; free_balance is what is left after it
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CycleSummary {
+    pub total_deposited: i128,
+    pub operating_fee: i128,
+    pub reserved: i128,
+    pub disbursed: i128,
+    pub free_balance: i128,
+    pub next_due_date: Option<u64>, // earliest unpaid due date, None once the cycle has ended
+    pub paid_bills: u32,
+    pub pending_bills: u32, // unpaid and not yet past the payment window
+    pub overdue_bills: u32, // unpaid and past the payment window
+}
+
+// One token's line in check_solvency; shortfall is 0 when the balance covers the liabilities
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SolvencyReport {
+    pub token: Address,
+    pub balance: i128,
+    pub liabilities: i128,
+    pub shortfall: i128,
+}
+
+// Outcome of one bill in a batch payment; error holds the Error code when not paid
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaymentResult {
+    pub bill_id: u64,
+    pub paid: bool,
+    pub error: Option<u32>,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
-    Admin,
-    PendingAdmin,
-    TransferExpiry,
+    Admin,        // single admin before the signer set, read only by migrate
+    PendingAdmin, // legacy admin transfer, removed by migrate
+    AdminSigners,
+    AdminThreshold,
+    AdminApprovals(AdminAction), // action -> Vec<signer> that approved it
+    RoleMember(Role, Address),
+    TransferExpiry, // legacy admin transfer, removed by migrate
+    ProposalCounter,
+    Proposal(u64),    // proposal_id -> ConfigProposal
+    PendingProposals, // Vec<proposal_id> awaiting execution or cancellation
     UsdcToken,
     FeeRecipient,
-    FeePercentage,
+    FeePercentage, // global fee before per-token fees, read only by migrate
+    AllowedTokens, // tokens new cycles can be funded in
+    TokenFeePercentage(Address), // token -> fee in basis points
+    PaymentWindow,
+    KeeperTip,
+    EarlyWithdrawalPenalty, // basis points, default 10%
+    Paused(PauseScope),
+    Liabilities(Address), // token -> amount owed on active cycles funded in it
+    LiabilityTokens,      // Vec<token> with a Liabilities entry
     CycleCounter,
     BillCounter,
-    Cycle(u64),           // cycle_id -> BillCycle
-    Bill(u64),            // bill_id -> Bill
-    UserCycles(Address),  // user -> Vec<cycle_id>
-    CycleBills(u64),      // cycle_id -> Vec<bill_id>
-    AllCycles,            // Global list of all cycle IDs (admin-only)
+    Cycle(u64),          // cycle_id -> BillCycle
+    Bill(u64),           // bill_id -> Bill
+    UserCycles(Address), // user -> Vec<cycle_id>
+    CycleBills(u64),     // cycle_id -> Vec<bill_id>
+    DueBills(u64),       // UTC day (timestamp / 86400) -> Vec<bill_id> due that day
+    Invoice(u64),        // bill_id -> Invoice for its current occurrence
+    AllCycles,           // Global list of all cycle IDs (signers and keepers)
     ReentrancyLock,
-}
\ No newline at end of file
+    Version,         // storage layout version
+    MigrationCursor, // index into AllCycles while a migration is in progress
+}
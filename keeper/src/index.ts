@@ -6,7 +6,7 @@ import { initializeContract, processDueBills } from './payment.js';
 dotenv.config();
 
 const requiredEnvVars = [
-  'ADMIN_SECRET_KEY',
+  'KEEPER_SECRET_KEY',
   'CONTRACT_ID',
   'RPC_URL',
   'NETWORK_PASSPHRASE',
@@ -20,17 +20,17 @@ for (const varName of requiredEnvVars) {
   }
 }
 
-let adminKeypair: Keypair;
+let keeperKeypair: Keypair;
 try {
-  adminKeypair = Keypair.fromSecret(process.env.ADMIN_SECRET_KEY!);
-  console.log(`✅ Admin public key: ${adminKeypair.publicKey()}\n`);
+  keeperKeypair = Keypair.fromSecret(process.env.KEEPER_SECRET_KEY!);
+  console.log(`✅ Keeper public key: ${keeperKeypair.publicKey()}\n`);
 } catch (error) {
-  console.error('❌ Invalid admin secret key');
+  console.error('❌ Invalid keeper secret key');
   process.exit(1);
 }
 
 const contract = initializeContract(
-  process.env.ADMIN_SECRET_KEY!,
+  process.env.KEEPER_SECRET_KEY!,
   process.env.CONTRACT_ID!,
   process.env.RPC_URL!,
   process.env.NETWORK_PASSPHRASE!
@@ -55,7 +55,7 @@ const runNow = process.argv.includes('--now');
 
 if (runNow) {
   console.log('🚀 Running manual payment check...\n');
-  processDueBills(contract, adminKeypair)
+  processDueBills(contract, keeperKeypair)
     .then((result) => {
       console.log('✅ Manual run completed');
       process.exit(0);
@@ -70,7 +70,7 @@ if (runNow) {
 
   cron.schedule(cronSchedule, async () => {
     try {
-      await processDueBills(contract, adminKeypair);
+      await processDueBills(contract, keeperKeypair);
     } catch (error) {
       console.error('❌ Error in scheduled job:', error);
     }
@@ -2,31 +2,34 @@ import * as Client from 'lockedin';
 import { Keypair } from '@stellar/stellar-sdk';
 import { Buffer } from 'buffer';
 
+// Bills paid per admin_pay_bills transaction
+const BATCH_SIZE = 25;
+
 export function initializeContract(
-  adminSecretKey: string,
+  keeperSecretKey: string,
   contractId: string,
   rpcUrl: string,
   networkPassphrase: string
 ): Client.Client {
-  const adminKeypair = Keypair.fromSecret(adminSecretKey);
+  const keeperKeypair = Keypair.fromSecret(keeperSecretKey);
 
   return new Client.Client({
     networkPassphrase,
     contractId,
     rpcUrl,
-    publicKey: adminKeypair.publicKey(),
+    publicKey: keeperKeypair.publicKey(),
   });
 }
 
-export async function getAllCycles(contract: Client.Client, adminKeypair: Keypair): Promise<bigint[]> {
+export async function getAllCycles(contract: Client.Client, keeperKeypair: Keypair): Promise<bigint[]> {
   try {
-    const tx = await contract.get_all_cycles();
+    const tx = await contract.get_all_cycles({ caller: keeperKeypair.publicKey() });
     const signed = await tx.signAuthEntries({
       signAuthEntry: async (entryXdr: string) => {
-        const signature = adminKeypair.sign(Buffer.from(entryXdr, 'base64'));
+        const signature = keeperKeypair.sign(Buffer.from(entryXdr, 'base64'));
         return {
           signedAuthEntry: signature.toString('base64'),
-          signerAddress: adminKeypair.publicKey()
+          signerAddress: keeperKeypair.publicKey()
         };
       }
     });
@@ -50,31 +53,61 @@ export async function getCycleBills(contract: Client.Client, cycleId: bigint): P
       return [];
     }
 
-    const bills = [];
-    for (const billId of billIds) {
-      try {
-        const billTx = await contract.get_bill({ bill_id: billId });
-        const billSimulated = await billTx.simulate();
-        const billData = (billSimulated.result as any)?.value || billSimulated.result;
-        bills.push(billData);
-      } catch (err) {
-        console.error(`Error fetching bill ${billId}:`, err);
-      }
-    }
-
-    return bills;
+    return await getBills(contract, billIds);
   } catch (error) {
     console.error(`Error getting bills for cycle ${cycleId}:`, error);
     return [];
   }
 }
 
-export function isBillDueToday(bill: any): boolean {
-  const now = Date.now();
-  const currentDayStart = Math.floor(now / 86400000) * 86400;
-  const dueDayStart = Math.floor(Number(bill.due_date) / 86400) * 86400;
+export async function getDueBillIds(contract: Client.Client, fromTs: bigint, toTs: bigint, pageSize: number = 50): Promise<bigint[]> {
+  const billIds: bigint[] = [];
+  let cursor = 0;
+
+  try {
+    while (true) {
+      const tx = await contract.get_due_bills({ from_ts: fromTs, to_ts: toTs, cursor, limit: pageSize });
+      const simulated = await tx.simulate();
+      const page: bigint[] = (simulated.result as any)?.value || simulated.result || [];
+
+      billIds.push(...page);
+      if (page.length < pageSize) {
+        break;
+      }
+      cursor += page.length;
+    }
+  } catch (error) {
+    console.error(`Error getting bills due between ${fromTs} and ${toTs}:`, error);
+  }
 
-  return currentDayStart === dueDayStart && !bill.is_paid;
+  return billIds;
+}
+
+export async function getBills(contract: Client.Client, billIds: bigint[]): Promise<any[]> {
+  const bills = [];
+  for (const billId of billIds) {
+    try {
+      const billTx = await contract.get_bill({ bill_id: billId });
+      const billSimulated = await billTx.simulate();
+      const billData = (billSimulated.result as any)?.value || billSimulated.result;
+      bills.push(billData);
+    } catch (err) {
+      console.error(`Error fetching bill ${billId}:`, err);
+    }
+  }
+  return bills;
+}
+
+export async function getOverdueWindowSeconds(contract: Client.Client): Promise<bigint> {
+  try {
+    const tx = await contract.payment_window();
+    const simulated = await tx.simulate();
+    const window = (simulated.result as any)?.value || simulated.result;
+    return BigInt(Number(window.days_after) * 86400);
+  } catch (error) {
+    console.error('Error getting payment window:', error);
+    return BigInt(0);
+  }
 }
 
 export function isBillDueSoon(bill: any, hoursAhead: number = 24): boolean {
@@ -86,18 +119,18 @@ export function isBillDueSoon(bill: any, hoursAhead: number = 24): boolean {
   return hoursUntilDue > 0 && hoursUntilDue <= hoursAhead && !bill.is_paid;
 }
 
-export async function payBill(contract: Client.Client, adminKeypair: Keypair, billId: bigint): Promise<{ success: boolean; billId: bigint; result?: any; error?: string }> {
+export async function payBill(contract: Client.Client, keeperKeypair: Keypair, billId: bigint): Promise<{ success: boolean; billId: bigint; result?: any; error?: string }> {
   try {
     console.log(`Paying bill ${billId}...`);
 
-    const tx = await contract.admin_pay_bill({ bill_id: billId });
+    const tx = await contract.admin_pay_bill({ caller: keeperKeypair.publicKey(), bill_id: billId });
 
     const signed = await tx.signAuthEntries({
       signAuthEntry: async (entryXdr: string) => {
-        const signature = adminKeypair.sign(Buffer.from(entryXdr, 'base64'));
+        const signature = keeperKeypair.sign(Buffer.from(entryXdr, 'base64'));
         return {
           signedAuthEntry: signature.toString('base64'),
-          signerAddress: adminKeypair.publicKey()
+          signerAddress: keeperKeypair.publicKey()
         };
       }
     });
@@ -112,39 +145,76 @@ export async function payBill(contract: Client.Client, adminKeypair: Keypair, bi
   }
 }
 
-export async function processDueBills(contract: Client.Client, adminKeypair: Keypair): Promise<{ processed: number; paid: number; failed: number }> {
+export async function payBills(contract: Client.Client, keeperKeypair: Keypair, billIds: bigint[]): Promise<{ billId: bigint; paid: boolean; error?: number }[]> {
+  try {
+    console.log(`Paying ${billIds.length} bill(s) in one batch...`);
+
+    const tx = await contract.admin_pay_bills({ caller: keeperKeypair.publicKey(), bill_ids: billIds });
+
+    const signed = await tx.signAuthEntries({
+      signAuthEntry: async (entryXdr: string) => {
+        const signature = keeperKeypair.sign(Buffer.from(entryXdr, 'base64'));
+        return {
+          signedAuthEntry: signature.toString('base64'),
+          signerAddress: keeperKeypair.publicKey()
+        };
+      }
+    });
+
+    const sent = await signed.send();
+    const results: any[] = (sent as any)?.result?.value || (sent as any)?.result || [];
+
+    return results.map((result) => ({
+      billId: result.bill_id,
+      paid: result.paid,
+      error: result.error ?? undefined,
+    }));
+  } catch (error: any) {
+    console.error('❌ Error paying batch:', error);
+    return billIds.map((billId) => ({ billId, paid: false }));
+  }
+}
+
+export async function processDueBills(contract: Client.Client, keeperKeypair: Keypair): Promise<{ processed: number; paid: number; failed: number }> {
   console.log('\n=== Processing Due Bills ===');
   console.log(`Timestamp: ${new Date().toISOString()}\n`);
 
-  const cycleIds = await getAllCycles(contract, adminKeypair);
+  // Bills missed on their due day stay payable until the window's days_after runs out
+  const now = BigInt(Math.floor(Date.now() / 1000));
+  const from = now - (await getOverdueWindowSeconds(contract));
+  const billIds = await getDueBillIds(contract, from, now);
 
-  if (!cycleIds || cycleIds.length === 0) {
-    console.log('No cycles found.');
+  if (billIds.length === 0) {
+    console.log('No bills due.');
     return { processed: 0, paid: 0, failed: 0 };
   }
 
-  console.log(`Found ${cycleIds.length} cycle(s)\n`);
+  console.log(`Found ${billIds.length} bill(s) due or overdue\n`);
 
   let processed = 0;
   let paid = 0;
   let failed = 0;
 
-  for (const cycleId of cycleIds) {
-    const bills = await getCycleBills(contract, cycleId);
-
-    for (const bill of bills) {
-      if (isBillDueToday(bill)) {
-        processed++;
-        console.log(`\nBill due today: ${bill.name} (ID: ${bill.id})`);
-        console.log(`Amount: ${Number(bill.amount) / 10_000_000} USDC`);
+  const bills = await getBills(contract, billIds);
+  const due = bills.filter((bill) => !bill.is_paid);
 
-        const result = await payBill(contract, adminKeypair, bill.id);
+  for (const bill of due) {
+    console.log(`\nBill due: ${bill.name} (ID: ${bill.id})`);
+    console.log(`Amount: ${Number(bill.amount) / 10_000_000} USDC`);
+  }
 
-        if (result.success) {
-          paid++;
-        } else {
-          failed++;
-        }
+  for (let i = 0; i < due.length; i += BATCH_SIZE) {
+    const batch = due.slice(i, i + BATCH_SIZE).map((bill) => bill.id);
+    const results = await payBills(contract, keeperKeypair, batch);
+
+    for (const result of results) {
+      processed++;
+      if (result.paid) {
+        paid++;
+        console.log(`✅ Bill ${result.billId} paid successfully`);
+      } else {
+        failed++;
+        console.error(`❌ Bill ${result.billId} not paid${result.error !== undefined ? ` (error #${result.error})` : ''}`);
       }
     }
   }
@@ -157,24 +227,14 @@ export async function processDueBills(contract: Client.Client, adminKeypair: Key
   return { processed, paid, failed };
 }
 
-export async function getBillsDueSoon(contract: Client.Client, adminKeypair: Keypair, hoursAhead: number = 24): Promise<any[]> {
-  const cycleIds = await getAllCycles(contract, adminKeypair);
+export async function getBillsDueSoon(contract: Client.Client, keeperKeypair: Keypair, hoursAhead: number = 24): Promise<any[]> {
+  const now = Math.floor(Date.now() / 1000);
+  const billIds = await getDueBillIds(contract, BigInt(now), BigInt(now + hoursAhead * 3600));
 
-  if (!cycleIds || cycleIds.length === 0) {
+  if (billIds.length === 0) {
     return [];
   }
 
-  const dueSoonBills = [];
-
-  for (const cycleId of cycleIds) {
-    const bills = await getCycleBills(contract, cycleId);
-
-    for (const bill of bills) {
-      if (isBillDueSoon(bill, hoursAhead)) {
-        dueSoonBills.push(bill);
-      }
-    }
-  }
-
-  return dueSoonBills;
-}
\ No newline at end of file
+  const bills = await getBills(contract, billIds);
+  return bills.filter((bill) => isBillDueSoon(bill, hoursAhead));
+}